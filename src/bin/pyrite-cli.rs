@@ -0,0 +1,241 @@
+//! Command-line tools for working on modules without starting the game.
+
+use anyhow::{Error, Result};
+use pyrite_box::{
+    module::{MaterialDefinition, Module},
+    modules::{format_module, read_module, validate_module, Severity},
+};
+use std::{path::Path, process::exit};
+
+const USAGE: &str = "Usage: pyrite-cli <command> <module directory> [options]
+
+Commands:
+  validate <module>          Check for broken references. Exits with 1 on errors.
+  list <module> [what]       List maps, events, materials, monsters, encounters
+                             or items (everything if omitted).
+  fmt <module> [--check]     Reformat every RON file, writing out defaulted fields.
+                             Files with comments are skipped. With --check, only
+                             list the files that would change, exiting with 1 if
+                             any do.
+  summary <module>           Print a Markdown summary of the module.";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(success) => {
+            if !success {
+                exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit(2);
+        }
+    }
+}
+
+/// Returns Ok(false) when the command ran but found problems.
+fn run(args: &[String]) -> Result<bool> {
+    if args.len() < 2 {
+        println!("{}", USAGE);
+        return Ok(false);
+    }
+    let path = Path::new(&args[1]);
+    let options = &args[2..];
+    match args[0].as_str() {
+        "validate" => validate(&read_module(path)?),
+        "list" => {
+            let what = options.first().map(|s| s.as_str());
+            list(&read_module(path)?, what)
+        }
+        "fmt" => fmt(path, options.iter().any(|o| o == "--check")),
+        "summary" => {
+            summary(&read_module(path)?);
+            Ok(true)
+        }
+        other => Err(Error::msg(format!(
+            "Unknown command '{}'\n\n{}",
+            other, USAGE
+        ))),
+    }
+}
+
+fn validate(module: &Module) -> Result<bool> {
+    let issues = validate_module(module);
+    for issue in issues.iter() {
+        println!("{}", issue);
+    }
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    println!(
+        "{}: {} errors, {} warnings",
+        module.name,
+        errors,
+        issues.len() - errors
+    );
+    Ok(errors == 0)
+}
+
+fn list(module: &Module, what: Option<&str>) -> Result<bool> {
+    let all = what.is_none();
+    let show = |section: &str| all || what == Some(section);
+    let mut shown = false;
+
+    if show("maps") {
+        shown = true;
+        println!("Maps:");
+        let mut maps: Vec<_> = module.maps.iter().collect();
+        maps.sort_by_key(|(idx, _)| **idx);
+        for (idx, map) in maps {
+            println!("  {:>3}  {} ({}x{})", idx, map.name, map.size.0, map.size.1);
+        }
+    }
+    if show("events") {
+        shown = true;
+        println!("Events:");
+        let mut events: Vec<_> = module.events.events.iter().collect();
+        events.sort_by(|a, b| a.filename.cmp(&b.filename).then(a.tag.cmp(&b.tag)));
+        for event in events {
+            println!(
+                "  {}  ({} steps, {})",
+                event.tag,
+                event.steps.len(),
+                event.filename
+            );
+        }
+    }
+    if show("materials") {
+        shown = true;
+        println!("Materials:");
+        let mut materials: Vec<_> = module.materials.iter().collect();
+        materials.sort_by_key(|(idx, _)| **idx);
+        for (idx, (name, material, _)) in materials {
+            let kind = match material {
+                MaterialDefinition::Color { r, g, b } => format!("color {},{},{}", r, g, b),
+                MaterialDefinition::Pbr { albedo, .. } => format!("PBR {}", albedo),
+            };
+            println!("  {:>3}  {} ({})", idx, name, kind);
+        }
+    }
+    if show("monsters") {
+        shown = true;
+        println!("Monsters:");
+        for tag in sorted_keys(module.monsters.keys()) {
+            let monster = &module.monsters[tag];
+            println!(
+                "  {}  {} (HD {}, AC {}, {} XP)",
+                tag, monster.name, monster.hit_dice, monster.armor_class, monster.xp
+            );
+        }
+    }
+    if show("encounters") {
+        shown = true;
+        println!("Encounters:");
+        for tag in sorted_keys(module.encounters.keys()) {
+            let groups: Vec<String> = module.encounters[tag]
+                .groups
+                .iter()
+                .map(|g| format!("{} {}", g.count, g.monster))
+                .collect();
+            println!("  {}  {}", tag, groups.join(", "));
+        }
+    }
+    if show("items") {
+        shown = true;
+        println!("Items:");
+        for tag in sorted_keys(module.items.keys()) {
+            println!("  {}  {}", tag, module.items[tag].name);
+        }
+    }
+
+    if !shown {
+        return Err(Error::msg(format!(
+            "Unknown list '{}'. Try maps, events, materials, monsters, encounters or items.",
+            what.unwrap_or_default()
+        )));
+    }
+    Ok(true)
+}
+
+fn fmt(path: &Path, check: bool) -> Result<bool> {
+    let report = format_module(path, check)?;
+    for file in report.changed.iter() {
+        if check {
+            println!("Needs formatting: {}", file.display());
+        } else {
+            println!("Formatted: {}", file.display());
+        }
+    }
+    for file in report.skipped.iter() {
+        println!("Skipped (has comments): {}", file.display());
+    }
+    Ok(!check || report.changed.is_empty())
+}
+
+fn summary(module: &Module) {
+    println!("# {}", module.name);
+    println!();
+    if !module.author.is_empty() {
+        println!("By {}", module.author);
+        println!();
+    }
+    if !module.description.is_empty() {
+        println!("{}", module.description);
+        println!();
+    }
+
+    let issues = validate_module(module);
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    println!("| | |");
+    println!("|---|---|");
+    println!("| Maps | {} |", module.maps.len());
+    println!("| Events | {} |", module.events.events.len());
+    println!("| Materials | {} |", module.materials.len());
+    println!("| Monsters | {} |", module.monsters.len());
+    println!("| Encounters | {} |", module.encounters.len());
+    println!("| Items | {} |", module.items.len());
+    println!("| Errors | {} |", errors);
+    println!("| Warnings | {} |", issues.len() - errors);
+    println!();
+
+    println!("## Maps");
+    println!();
+    let mut maps: Vec<_> = module.maps.iter().collect();
+    maps.sort_by_key(|(idx, _)| **idx);
+    for (idx, map) in maps {
+        let start = if *idx == module.starting_map_idx {
+            " (start)"
+        } else {
+            ""
+        };
+        println!(
+            "- {}: {} ({}x{}, {} doors){}",
+            idx,
+            map.name,
+            map.size.0,
+            map.size.1,
+            map.doors.len(),
+            start
+        );
+    }
+    println!();
+
+    println!("## Events");
+    println!();
+    let mut tags: Vec<&String> = module.events.events.iter().map(|e| &e.tag).collect();
+    tags.sort();
+    for tag in tags {
+        println!("- {}", tag);
+    }
+}
+
+fn sorted_keys<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut keys: Vec<&String> = keys.collect();
+    keys.sort();
+    keys
+}
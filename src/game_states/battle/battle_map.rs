@@ -4,7 +4,9 @@ use crate::{
     module::Direction,
     region::{
         region_assets::RegionAssets,
-        region_map::{RegionBoundaryType, RegionMap, RegionTileType},
+        region_map::{
+            line_of_sight, PathGrid, RegionBoundaryType, RegionMap, RegionTileType, SightGrid,
+        },
     },
 };
 use bevy::prelude::*;
@@ -40,6 +42,22 @@ impl BattleMap {
         (y * BATTLE_WIDTH) + x
     }
 
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < BATTLE_WIDTH as i32 && y < BATTLE_HEIGHT as i32
+    }
+
+    /// Can a combatant stand on this tile?
+    pub fn is_open(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y)
+            && self.tiles[self.battle_tile_idx(x as usize, y as usize)] == BattleTile::Open
+    }
+
+    /// Can a combatant on `from` see `to`? Walls block the view; other
+    /// combatants don't.
+    pub fn can_see(&self, from: (i32, i32), to: (i32, i32)) -> bool {
+        line_of_sight(self, from, to)
+    }
+
     pub fn setup_region_coordinates(mut self, player: &WanderingPlayer) -> Self {
         self.region_coords.0 = player.x - 1;
         self.region_coords.1 = player.y - 1;
@@ -50,7 +68,7 @@ impl BattleMap {
         for y in self.region_coords.1..self.region_coords.1 + 3 {
             for x in self.region_coords.0..self.region_coords.0 + 3 {
                 if x < map.size.0 as i32 && y < map.size.1 as i32 && x >= 0 && y >= 0 {
-                    let region_idx = ((y * map.size.1 as i32) + x) as usize;
+                    let region_idx = map.tile_idx(x, y);
                     match map.tiles[region_idx].tile_type {
                         RegionTileType::Solid => self.make_region_tile_solid(x, y),
                         _ => {
@@ -59,7 +77,7 @@ impl BattleMap {
                             // Carve out center
                             self.make_region_tile_open(x, y);
                             // Clear exits
-                            self.process_exits(map, x, y, region_idx);
+                            self.process_exits(map, x, y);
                         }
                     }
                 } else {
@@ -95,14 +113,14 @@ impl BattleMap {
         }
     }
 
-    fn process_exits(&mut self, map: &RegionMap, x: i32, y: i32, idx: usize) {
+    fn process_exits(&mut self, map: &RegionMap, x: i32, y: i32) {
         let top_left = self.battle_tile_idx(
             (x - self.region_coords.0) as usize * TILE_WIDTH,
             (y - self.region_coords.1) as usize * TILE_HEIGHT,
         );
 
         // North
-        match map.tiles[idx].boundaries[Direction::North.to_exit_index()].0 {
+        match map.effective_boundary(x, y, Direction::North) {
             RegionBoundaryType::None => {
                 for x in 1..TILE_WIDTH - 1 {
                     self.tiles[top_left + x] = BattleTile::Open;
@@ -121,7 +139,7 @@ impl BattleMap {
 
         // South
         let bottom_left = top_left + (BATTLE_WIDTH * (TILE_HEIGHT - 1));
-        match map.tiles[idx].boundaries[Direction::South.to_exit_index()].0 {
+        match map.effective_boundary(x, y, Direction::South) {
             RegionBoundaryType::None => {
                 for x in 1..TILE_WIDTH - 1 {
                     self.tiles[bottom_left + x] = BattleTile::Open;
@@ -139,7 +157,7 @@ impl BattleMap {
         }
 
         // West
-        match map.tiles[idx].boundaries[Direction::West.to_exit_index()].0 {
+        match map.effective_boundary(x, y, Direction::West) {
             RegionBoundaryType::None => {
                 for y in 1..TILE_HEIGHT - 1 {
                     self.tiles[top_left + (y * BATTLE_WIDTH)] = BattleTile::Open;
@@ -157,7 +175,7 @@ impl BattleMap {
         }
 
         // East
-        match map.tiles[idx].boundaries[Direction::East.to_exit_index()].0 {
+        match map.effective_boundary(x, y, Direction::East) {
             RegionBoundaryType::None => {
                 for y in 1..TILE_HEIGHT - 1 {
                     self.tiles[top_left + (y * BATTLE_WIDTH) + (TILE_WIDTH - 1)] = BattleTile::Open;
@@ -176,7 +194,7 @@ impl BattleMap {
         }
     }
 
-    fn tile_to_screen(&self, x: usize, y: usize) -> Vec2 {
+    pub fn tile_to_screen(&self, x: usize, y: usize) -> Vec2 {
         let mut result = Vec2::new(x as f32 * 32.0, y as f32 * 32.0)
             - Vec2::new(
                 (BATTLE_WIDTH as f32 / 2.0) * 32.0,
@@ -209,3 +227,27 @@ impl BattleMap {
         }
     }
 }
+
+impl SightGrid for BattleMap {
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        BattleMap::in_bounds(self, x, y)
+    }
+
+    fn blocks_sight(&self, _x: i32, _y: i32, _direction: Direction) -> bool {
+        false
+    }
+
+    fn is_opaque(&self, x: i32, y: i32) -> bool {
+        !self.is_open(x, y)
+    }
+}
+
+impl PathGrid for BattleMap {
+    fn exits(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        [(0, -1), (0, 1), (-1, 0), (1, 0)]
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|(nx, ny)| self.is_open(*nx, *ny))
+            .collect()
+    }
+}
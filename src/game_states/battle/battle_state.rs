@@ -0,0 +1,438 @@
+use super::{
+    battle_map::{BattleMap, BATTLE_HEIGHT, BATTLE_WIDTH},
+    combatant::{Combatant, Team},
+};
+use crate::{
+    module::Placement,
+    region::region_map::{find_path, PathGrid},
+};
+use bevy::prelude::*;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::time::Duration;
+
+const MS_AI_DELAY: u64 = 400;
+const LOG_LENGTH: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BattleOutcome {
+    Victory,
+    Defeat,
+    Fled,
+}
+
+impl BattleOutcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BattleOutcome::Victory => "Victory",
+            BattleOutcome::Defeat => "Defeat",
+            BattleOutcome::Fled => "Fled",
+        }
+    }
+}
+
+/// The state of a battle in progress. Inserted as a resource when
+/// entering `AppState::Battle`.
+pub struct BattleResource {
+    pub map: BattleMap,
+    pub combatants: Vec<Combatant>,
+    pub initiative: Vec<usize>,
+    pub turn: usize,
+    pub round: u32,
+    pub moves_left: i32,
+    pub log: Vec<String>,
+    pub outcome: Option<BattleOutcome>,
+    pub rng: RandomNumberGenerator,
+    pub ai_timer: Timer,
+}
+
+impl BattleResource {
+    pub fn new(map: BattleMap, party: Vec<Combatant>, monsters: Vec<Combatant>) -> Self {
+        let mut battle = Self {
+            map,
+            combatants: party.into_iter().chain(monsters.into_iter()).collect(),
+            initiative: Vec::new(),
+            turn: 0,
+            round: 0,
+            moves_left: 0,
+            log: Vec::new(),
+            outcome: None,
+            rng: RandomNumberGenerator::new(),
+            ai_timer: Timer::new(Duration::from_millis(MS_AI_DELAY), false),
+        };
+        battle.place_combatants();
+        battle.check_outcome();
+        battle.new_round();
+        battle.skip_fallen();
+        battle.start_turn();
+        battle
+    }
+
+    /// Party members start near the middle of the map (where the party was
+    /// standing). Monsters start far away, right next to the party or at
+    /// random depending on their encounter's placement. The fallen aren't
+    /// placed, and anyone left without a tile is marked absent.
+    fn place_combatants(&mut self) {
+        let center = (BATTLE_WIDTH as i32 / 2, BATTLE_HEIGHT as i32 / 2);
+        let mut open_tiles = Vec::new();
+        for y in 0..BATTLE_HEIGHT as i32 {
+            for x in 0..BATTLE_WIDTH as i32 {
+                if self.map.is_open(x, y) {
+                    open_tiles.push((x, y));
+                }
+            }
+        }
+        open_tiles.sort_by_key(|(x, y)| (x - center.0).pow(2) + (y - center.1).pow(2));
+
+        let mut used = Vec::new();
+        let rng = &mut self.rng;
+        for combatant in self.combatants.iter_mut().filter(|c| c.is_alive()) {
+            let tile = match combatant.placement {
+                Placement::Near => open_tiles.iter().find(|t| !used.contains(*t)),
+                Placement::Far => open_tiles.iter().rev().find(|t| !used.contains(*t)),
+                Placement::Random => {
+                    let available: Vec<&(i32, i32)> =
+                        open_tiles.iter().filter(|t| !used.contains(*t)).collect();
+                    rng.random_slice_entry(&available).copied()
+                }
+            };
+            if let Some(tile) = tile {
+                combatant.position = *tile;
+                used.push(*tile);
+            } else {
+                // Nowhere left to stand - they sit this one out
+                combatant.absent = true;
+            }
+        }
+    }
+
+    fn new_round(&mut self) {
+        self.round += 1;
+        let rng = &mut self.rng;
+        let mut rolls: Vec<(usize, i32)> = self
+            .combatants
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, rng.roll_dice(1, 10) + c.initiative_bonus))
+            .collect();
+        rolls.sort_by(|a, b| b.1.cmp(&a.1));
+        self.initiative = rolls.iter().map(|(i, _)| *i).collect();
+        self.turn = 0;
+        self.add_log(format!("Round {} begins.", self.round));
+    }
+
+    fn start_turn(&mut self) {
+        if let Some(idx) = self.current() {
+            self.moves_left = self.combatants[idx].movement;
+            self.ai_timer = Timer::new(Duration::from_millis(MS_AI_DELAY), false);
+        }
+    }
+
+    /// The combatant whose turn it is.
+    pub fn current(&self) -> Option<usize> {
+        self.initiative.get(self.turn).copied()
+    }
+
+    pub fn current_team(&self) -> Option<Team> {
+        self.current().map(|idx| self.combatants[idx].team)
+    }
+
+    /// Advance to the next living combatant, starting a new round when
+    /// everyone has acted.
+    pub fn end_turn(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+        self.turn += 1;
+        self.skip_fallen();
+        self.start_turn();
+    }
+
+    fn skip_fallen(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+        loop {
+            if self.turn >= self.initiative.len() {
+                self.new_round();
+            }
+            if let Some(idx) = self.current() {
+                if self.combatants[idx].is_fighting() {
+                    break;
+                }
+            }
+            self.turn += 1;
+        }
+    }
+
+    pub fn add_log<S: ToString>(&mut self, line: S) {
+        self.log.push(line.to_string());
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+
+    /// The fighting combatant standing on a tile, if any.
+    pub fn combatant_at(&self, x: i32, y: i32) -> Option<usize> {
+        self.combatants
+            .iter()
+            .position(|c| c.is_fighting() && c.position == (x, y))
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> f32 {
+        let (ax, ay) = self.combatants[a].position;
+        let (bx, by) = self.combatants[b].position;
+        (((ax - bx).pow(2) + (ay - by).pow(2)) as f32).sqrt()
+    }
+
+    fn is_adjacent(&self, a: usize, b: usize) -> bool {
+        let (ax, ay) = self.combatants[a].position;
+        let (bx, by) = self.combatants[b].position;
+        (ax - bx).abs() + (ay - by).abs() == 1
+    }
+
+    /// The closest living member of the other team.
+    pub fn nearest_enemy(&self, idx: usize) -> Option<usize> {
+        let team = self.combatants[idx].team;
+        self.combatants
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_fighting() && c.team != team)
+            .map(|(i, _)| i)
+            .min_by(|a, b| {
+                self.distance(idx, *a)
+                    .partial_cmp(&self.distance(idx, *b))
+                    .unwrap()
+            })
+    }
+
+    /// The closest enemy inside the range of `idx`'s ranged attack, if
+    /// there is a clear shot at it.
+    pub fn ranged_target(&self, idx: usize) -> Option<usize> {
+        let ranged = self.combatants[idx].ranged.as_ref()?;
+        let target = self.nearest_enemy(idx)?;
+        if self.distance(idx, target) <= ranged.range as f32
+            && self.map.can_see(
+                self.combatants[idx].position,
+                self.combatants[target].position,
+            )
+        {
+            Some(target)
+        } else {
+            None
+        }
+    }
+
+    /// Move the current combatant one tile. Moving into an enemy attacks it
+    /// and ends the turn.
+    pub fn try_step(&mut self, dx: i32, dy: i32) {
+        if self.outcome.is_some() {
+            return;
+        }
+        if let Some(idx) = self.current() {
+            let (x, y) = self.combatants[idx].position;
+            let (nx, ny) = (x + dx, y + dy);
+            if let Some(other) = self.combatant_at(nx, ny) {
+                if self.combatants[other].team != self.combatants[idx].team {
+                    self.attack(idx, other, false);
+                    self.end_turn();
+                }
+            } else if self.moves_left > 0 && self.map.is_open(nx, ny) {
+                self.combatants[idx].position = (nx, ny);
+                self.moves_left -= 1;
+            }
+        }
+    }
+
+    /// The current combatant shoots at the nearest enemy in range, ending
+    /// its turn.
+    pub fn fire(&mut self) {
+        if self.outcome.is_some() {
+            return;
+        }
+        if let Some(idx) = self.current() {
+            if let Some(target) = self.ranged_target(idx) {
+                self.attack(idx, target, true);
+                self.end_turn();
+            }
+        }
+    }
+
+    pub fn attack(&mut self, attacker: usize, target: usize, ranged: bool) {
+        let attack = if ranged {
+            self.combatants[attacker].ranged.clone().unwrap()
+        } else {
+            self.combatants[attacker].melee.clone()
+        };
+        let roll = self.rng.roll_dice(1, 20) + self.combatants[attacker].to_hit;
+        let attacker_name = self.combatants[attacker].name.clone();
+        let target_name = self.combatants[target].name.clone();
+
+        if roll >= self.combatants[target].armor_class {
+            let damage = i32::max(1, attack.damage.roll(&mut self.rng));
+            self.combatants[target].hp -= damage;
+            self.add_log(format!(
+                "{} hits {} with {} for {} damage.",
+                attacker_name, target_name, attack.name, damage
+            ));
+            if !self.combatants[target].is_alive() {
+                self.add_log(format!("{} is slain!", target_name));
+            }
+        } else {
+            self.add_log(format!(
+                "{} misses {} with {}.",
+                attacker_name, target_name, attack.name
+            ));
+        }
+        self.check_outcome();
+    }
+
+    fn check_outcome(&mut self) {
+        let party_alive = self
+            .combatants
+            .iter()
+            .any(|c| c.team == Team::Party && c.is_fighting());
+        let monsters_alive = self
+            .combatants
+            .iter()
+            .any(|c| c.team == Team::Monsters && c.is_fighting());
+        if !monsters_alive {
+            self.outcome = Some(BattleOutcome::Victory);
+            self.add_log("The enemy has been defeated!");
+        } else if !party_alive {
+            self.outcome = Some(BattleOutcome::Defeat);
+            self.add_log("Your party has fallen...");
+        }
+    }
+
+    /// Experience and gold earned from the monsters slain so far
+    pub fn rewards(&self) -> (i32, i32) {
+        self.combatants
+            .iter()
+            .filter(|c| c.team == Team::Monsters && !c.absent && !c.is_alive())
+            .fold((0, 0), |(xp, gold), c| (xp + c.xp, gold + c.gold))
+    }
+
+    pub fn flee(&mut self) {
+        if self.outcome.is_none() {
+            self.outcome = Some(BattleOutcome::Fled);
+            self.add_log("Your party flees!");
+        }
+    }
+
+    /// Monsters attack if they can, otherwise close in on the nearest
+    /// party member.
+    pub fn monster_turn(&mut self) {
+        if let Some(idx) = self.current() {
+            if let Some(target) = self.nearest_enemy(idx) {
+                if self.is_adjacent(idx, target) {
+                    self.attack(idx, target, false);
+                } else if self.ranged_target(idx).is_some() {
+                    self.attack(idx, target, true);
+                } else {
+                    self.walk_towards(idx, target);
+                    if self.is_adjacent(idx, target) {
+                        self.attack(idx, target, false);
+                    }
+                }
+            }
+            self.end_turn();
+        }
+    }
+
+    /// Follows the shortest path around walls and other combatants towards
+    /// `target`, stopping next to it or when out of moves.
+    fn walk_towards(&mut self, idx: usize, target: usize) {
+        let from = self.combatants[idx].position;
+        let goal = self.combatants[target].position;
+        let path = find_path(&MoveGrid { battle: self, goal }, from, goal);
+        for step in path.unwrap_or_default() {
+            if step == goal || self.moves_left <= 0 {
+                break;
+            }
+            self.combatants[idx].position = step;
+            self.moves_left -= 1;
+        }
+    }
+}
+
+/// The battle map as seen by someone on the move: living combatants other
+/// than the one being walked to are in the way.
+struct MoveGrid<'a> {
+    battle: &'a BattleResource,
+    goal: (i32, i32),
+}
+
+impl<'a> PathGrid for MoveGrid<'a> {
+    fn exits(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        self.battle
+            .map
+            .exits(x, y)
+            .into_iter()
+            .filter(|(nx, ny)| {
+                (*nx, *ny) == self.goal || self.battle.combatant_at(*nx, *ny).is_none()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_states::battle::{battle_map::BattleTile, combatant::Attack};
+    use crate::module::Dice;
+
+    fn combatant(team: Team, hp: i32) -> Combatant {
+        Combatant {
+            name: String::new(),
+            team,
+            hp,
+            max_hp: 10,
+            armor_class: 10,
+            to_hit: 0,
+            initiative_bonus: 0,
+            movement: 6,
+            melee: Attack {
+                name: "Fists".to_string(),
+                damage: Dice::new(1, 2, 0),
+                range: 1,
+            },
+            ranged: None,
+            position: (0, 0),
+            placement: if team == Team::Party {
+                Placement::Near
+            } else {
+                Placement::Far
+            },
+            color: (0, 0, 0),
+            sprite: None,
+            xp: 100,
+            gold: 10,
+            absent: false,
+        }
+    }
+
+    #[test]
+    fn overfilled_map_leaves_combatants_absent() {
+        let mut map = BattleMap::new();
+        map.tiles.iter_mut().for_each(|t| *t = BattleTile::Wall);
+        for x in 0..3 {
+            let idx = map.battle_tile_idx(x, 0);
+            map.tiles[idx] = BattleTile::Open;
+        }
+        let party = vec![
+            combatant(Team::Party, 0),
+            combatant(Team::Party, 5),
+            combatant(Team::Party, 6),
+        ];
+        let monsters = (0..3).map(|_| combatant(Team::Monsters, 4)).collect();
+        let battle = BattleResource::new(map, party, monsters);
+
+        // The fallen don't take a tile from the living
+        let absent: Vec<bool> = battle.combatants.iter().map(|c| c.absent).collect();
+        assert_eq!(absent, vec![false, false, false, false, true, true]);
+        let hp: Vec<i32> = battle.combatants.iter().map(|c| c.hp).collect();
+        assert_eq!(hp, vec![0, 5, 6, 4, 4, 4]);
+        assert!(battle.outcome.is_none());
+        assert_eq!(battle.rewards(), (0, 0));
+    }
+}
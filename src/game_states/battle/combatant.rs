@@ -0,0 +1,199 @@
+use crate::{
+    game_states::{CharacterClass, CharacterHeader},
+    module::{Dice, EquipSlot, ItemKind, Module, Placement},
+};
+use bracket_random::prelude::RandomNumberGenerator;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Team {
+    Party,
+    Monsters,
+}
+
+#[derive(Clone, Debug)]
+pub struct Attack {
+    pub name: String,
+    pub damage: Dice,
+    /// Range in battle tiles. Melee attacks have a range of 1.
+    pub range: i32,
+}
+
+/// Someone taking part in a battle, from either side.
+#[derive(Clone, Debug)]
+pub struct Combatant {
+    pub name: String,
+    pub team: Team,
+    pub hp: i32,
+    pub max_hp: i32,
+    /// Ascending armor class: an attack hits if d20 + to_hit >= armor_class.
+    pub armor_class: i32,
+    pub to_hit: i32,
+    pub initiative_bonus: i32,
+    pub movement: i32,
+    pub melee: Attack,
+    pub ranged: Option<Attack>,
+    pub position: (i32, i32),
+    pub placement: Placement,
+    pub color: (u8, u8, u8),
+    /// Image file for the battle sprite
+    pub sprite: Option<String>,
+    pub xp: i32,
+    pub gold: i32,
+    /// There was nowhere free to stand, so they sit this battle out.
+    pub absent: bool,
+}
+
+impl Combatant {
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    /// Alive and on the battle map.
+    pub fn is_fighting(&self) -> bool {
+        !self.absent && self.is_alive()
+    }
+}
+
+/// Weapons used by each class when nothing is equipped.
+fn class_attacks(class: CharacterClass) -> (Attack, Option<Attack>) {
+    let attack = |name: &str, damage: Dice, range: i32| Attack {
+        name: name.to_string(),
+        damage,
+        range,
+    };
+    match class {
+        CharacterClass::Fighter => (attack("Long Sword", Dice::new(1, 8, 0), 1), None),
+        CharacterClass::Cleric => (attack("Mace", Dice::new(1, 6, 0), 1), None),
+        CharacterClass::Thief => (
+            attack("Short Sword", Dice::new(1, 6, 0), 1),
+            Some(attack("Sling", Dice::new(1, 4, 0), 6)),
+        ),
+        CharacterClass::MagicUser => (
+            attack("Dagger", Dice::new(1, 4, 0), 1),
+            Some(attack("Darts", Dice::new(1, 3, 0), 5)),
+        ),
+    }
+}
+
+/// The attack granted by the weapon a character has in `slot`, if any.
+fn equipped_attack(module: &Module, chr: &CharacterHeader, slot: EquipSlot) -> Option<Attack> {
+    let item = module.items.get(chr.equipment.get(&slot)?)?;
+    match item.kind {
+        ItemKind::Weapon { damage, range } => Some(Attack {
+            name: item.name.clone(),
+            damage,
+            range: i32::max(1, range),
+        }),
+        _ => None,
+    }
+}
+
+/// Builds combatants for the party, in party order. Fallen characters are
+/// included (with no hit points) so that indices line up with the party.
+pub fn party_combatants(module: &Module, party: &[CharacterHeader]) -> Vec<Combatant> {
+    party
+        .iter()
+        .map(|chr| {
+            let (class_melee, class_ranged) = class_attacks(chr.class);
+            let melee = equipped_attack(module, chr, EquipSlot::Melee).unwrap_or(class_melee);
+            let ranged = equipped_attack(module, chr, EquipSlot::Ranged).or(class_ranged);
+            let armor: i32 = chr
+                .equipment
+                .values()
+                .filter_map(|tag| match module.items.get(tag).map(|item| &item.kind) {
+                    Some(ItemKind::Armor { armor_class, .. }) => Some(*armor_class),
+                    _ => None,
+                })
+                .sum();
+            Combatant {
+                name: chr.name.clone(),
+                team: Team::Party,
+                hp: chr.hp,
+                max_hp: chr.max_hp,
+                armor_class: chr.armor_class() + armor,
+                to_hit: chr.to_hit(),
+                initiative_bonus: chr.initiative_bonus(),
+                movement: chr.movement(),
+                melee,
+                ranged,
+                position: (0, 0),
+                placement: Placement::Near,
+                color: (64, 128, 255),
+                sprite: None,
+                xp: 0,
+                gold: 0,
+                absent: false,
+            }
+        })
+        .collect()
+}
+
+/// Rolls up the monsters for an encounter defined in the module.
+pub fn encounter_monsters(module: &Module, encounter_tag: &str) -> Vec<Combatant> {
+    let mut rng = RandomNumberGenerator::new();
+    let mut result = Vec::new();
+
+    if let Some(encounter) = module.encounters.get(encounter_tag) {
+        for group in encounter.groups.iter() {
+            if let Some(template) = module.monsters.get(&group.monster) {
+                let count = i32::max(1, group.count.roll(&mut rng));
+                let sprite = template.sprite.as_ref().and_then(|key| {
+                    module
+                        .sprites
+                        .iter()
+                        .find(|(k, _)| k == key)
+                        .map(|(_, file)| file.clone())
+                });
+                let melee = template.attacks.iter().find(|a| a.range <= 1);
+                let ranged = template.attacks.iter().find(|a| a.range > 1);
+
+                for i in 0..count {
+                    let hp = i32::max(1, template.hit_dice.roll(&mut rng));
+                    result.push(Combatant {
+                        name: if count > 1 {
+                            format!("{} {}", template.name, i + 1)
+                        } else {
+                            template.name.clone()
+                        },
+                        team: Team::Monsters,
+                        hp,
+                        max_hp: hp,
+                        armor_class: template.armor_class,
+                        to_hit: template.to_hit,
+                        initiative_bonus: template.initiative_bonus,
+                        movement: template.movement,
+                        melee: melee
+                            .map(|a| Attack {
+                                name: a.name.clone(),
+                                damage: a.damage,
+                                range: 1,
+                            })
+                            .unwrap_or_else(|| Attack {
+                                name: "Claws".to_string(),
+                                damage: Dice::new(1, 2, 0),
+                                range: 1,
+                            }),
+                        ranged: ranged.map(|a| Attack {
+                            name: a.name.clone(),
+                            damage: a.damage,
+                            range: a.range,
+                        }),
+                        position: (0, 0),
+                        placement: group.placement,
+                        color: (255, 64, 64),
+                        sprite: sprite.clone(),
+                        xp: template.xp,
+                        gold: i32::max(0, template.gold.roll(&mut rng)),
+                        absent: false,
+                    });
+                }
+            } else {
+                println!("Encounter error: monster {} not found", group.monster);
+            }
+        }
+    } else {
+        println!("Encounter error: {} not found", encounter_tag);
+    }
+
+    result
+}
@@ -1,27 +1,269 @@
-use crate::region::region_assets::RegionAssets;
+use crate::{module::game_events::GameValue, region::region_assets::RegionAssets, AppState};
 use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Color32, Pos2},
+    EguiContext,
+};
 
-use self::battle_map::{BattleMap, BattleTile, BATTLE_HEIGHT, BATTLE_WIDTH};
-use super::{WanderResource, WanderingPlayer};
+use self::{
+    battle_map::BattleMap,
+    battle_state::{BattleOutcome, BattleResource},
+    combatant::{encounter_monsters, party_combatants, Team},
+};
+use super::{
+    gamelog::{GameLog, DEFAULT_TEXT_COLOR},
+    WanderResource, WanderingPlayer,
+};
 mod battle_map;
+mod battle_state;
+mod combatant;
 
 #[derive(Component)]
 pub struct BattleComponent {}
 
+/// Links a battle sprite to its index in `BattleResource::combatants`
+#[derive(Component)]
+pub struct CombatantSprite(pub usize);
+
+#[derive(Component)]
+pub struct TurnMarker {}
+
+const COMBATANT_SIZE: f32 = 24.0;
+
 pub fn start_battle(
     mut commands: Commands,
-    wander: Res<WanderResource>,
+    mut wander: ResMut<WanderResource>,
     player_query: Query<&WanderingPlayer>,
     assets: Res<RegionAssets>,
+    asset_server: Res<AssetServer>,
 ) {
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(BattleComponent {});
 
     // Obtain the player's map location
-    let player_pos = player_query.iter().nth(0).unwrap();
+    let player_pos = player_query.iter().next().unwrap();
     let current_map_idx = wander.map_idx;
     let current_map = &wander.module.maps[&current_map_idx];
     let battle_map = BattleMap::new()
         .setup_region_coordinates(player_pos)
         .build_from_region(current_map);
     battle_map.spawn_map_tiles(&mut commands, &assets);
+
+    let encounter = wander.battle_encounter.take().unwrap_or_default();
+    let monsters = encounter_monsters(&wander.module, &encounter);
+    let party = party_combatants(&wander.module, &wander.party);
+    let battle = BattleResource::new(battle_map, party, monsters);
+    for (i, combatant) in battle.combatants.iter().enumerate() {
+        let (r, g, b) = combatant.color;
+        let mut bundle = SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb_u8(r, g, b),
+                custom_size: Some(Vec2::new(COMBATANT_SIZE, COMBATANT_SIZE)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            ..Default::default()
+        };
+        if let Some(file) = &combatant.sprite {
+            bundle.sprite.color = Color::WHITE;
+            bundle.texture = asset_server.load(file.as_str());
+        }
+        commands
+            .spawn_bundle(bundle)
+            .insert(BattleComponent {})
+            .insert(CombatantSprite(i));
+    }
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::YELLOW,
+                custom_size: Some(Vec2::new(COMBATANT_SIZE + 6.0, COMBATANT_SIZE + 6.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..Default::default()
+        })
+        .insert(BattleComponent {})
+        .insert(TurnMarker {});
+
+    commands.insert_resource(battle);
+}
+
+/// Keyboard control of party members: arrows move (or attack by moving into
+/// an enemy), F fires a ranged weapon, space ends the turn.
+pub fn battle_input(keyboard_input: Res<Input<KeyCode>>, mut battle: ResMut<BattleResource>) {
+    if battle.outcome.is_some() || battle.current_team() != Some(Team::Party) {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        battle.try_step(0, -1);
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        battle.try_step(0, 1);
+    } else if keyboard_input.just_pressed(KeyCode::Left) {
+        battle.try_step(-1, 0);
+    } else if keyboard_input.just_pressed(KeyCode::Right) {
+        battle.try_step(1, 0);
+    } else if keyboard_input.just_pressed(KeyCode::F) {
+        battle.fire();
+    } else if keyboard_input.just_pressed(KeyCode::Space) {
+        battle.end_turn();
+    }
+}
+
+/// Monsters act after a short delay, so the player can follow what happened.
+pub fn battle_ai(time: Res<Time>, mut battle: ResMut<BattleResource>) {
+    if battle.outcome.is_some() || battle.current_team() != Some(Team::Monsters) {
+        return;
+    }
+
+    battle.ai_timer.tick(time.delta());
+    if battle.ai_timer.finished() {
+        battle.monster_turn();
+    }
+}
+
+pub fn battle_render(
+    battle: Res<BattleResource>,
+    mut sprite_query: Query<(&CombatantSprite, &mut Transform, &mut Visibility)>,
+    mut marker_query: Query<&mut Transform, (With<TurnMarker>, Without<CombatantSprite>)>,
+) {
+    sprite_query
+        .iter_mut()
+        .for_each(|(cs, mut trans, mut visible)| {
+            let combatant = &battle.combatants[cs.0];
+            let pos = battle
+                .map
+                .tile_to_screen(combatant.position.0 as usize, combatant.position.1 as usize);
+            trans.translation.x = pos.x;
+            trans.translation.y = pos.y;
+            visible.is_visible = combatant.is_fighting();
+        });
+
+    if let Some(idx) = battle.current() {
+        let (x, y) = battle.combatants[idx].position;
+        let pos = battle.map.tile_to_screen(x as usize, y as usize);
+        marker_query.iter_mut().for_each(|mut trans| {
+            trans.translation.x = pos.x;
+            trans.translation.y = pos.y;
+        });
+    }
+}
+
+pub fn battle_ui(
+    egui_context: ResMut<EguiContext>,
+    mut battle: ResMut<BattleResource>,
+    mut wander: ResMut<WanderResource>,
+    mut log: ResMut<GameLog>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let mut finished = None;
+
+    egui::Window::new("Battle")
+        .resizable(false)
+        .title_bar(true)
+        .fixed_pos(Pos2::new(10.0, 60.0))
+        .fixed_size(egui::Vec2::new(220.0, 600.0))
+        .show(egui_context.ctx(), |ui| {
+            ui.label(format!("Round {}", battle.round));
+            if let Some(idx) = battle.current() {
+                let current = &battle.combatants[idx];
+                ui.colored_label(Color32::YELLOW, format!("{}'s turn", current.name));
+                if current.team == Team::Party {
+                    ui.label(format!("Moves left: {}", battle.moves_left));
+                }
+            }
+            ui.separator();
+
+            for combatant in battle.combatants.iter() {
+                let color = if !combatant.is_fighting() {
+                    Color32::DARK_GRAY
+                } else if combatant.team == Team::Party {
+                    Color32::LIGHT_BLUE
+                } else {
+                    Color32::LIGHT_RED
+                };
+                ui.colored_label(
+                    color,
+                    format!("{}: {}/{}", combatant.name, combatant.hp, combatant.max_hp),
+                );
+            }
+            ui.separator();
+
+            if let Some(outcome) = battle.outcome {
+                ui.heading(outcome.name());
+                if ui.button("Continue").clicked() {
+                    finished = Some(outcome);
+                }
+            } else if battle.current_team() == Some(Team::Party) {
+                ui.label("Arrows: move or attack");
+                let idx = battle.current().unwrap();
+                if battle.ranged_target(idx).is_some() && ui.button("Fire (F)").clicked() {
+                    battle.fire();
+                }
+                if ui.button("End Turn (Space)").clicked() {
+                    battle.end_turn();
+                }
+                if ui.button("Flee").clicked() {
+                    battle.flee();
+                }
+            }
+            ui.separator();
+
+            for line in battle.log.iter() {
+                ui.label(line);
+            }
+        });
+
+    if let Some(outcome) = finished {
+        // Report back to the script that started the battle
+        wander.variables.set(
+            "battle_victory",
+            GameValue::Flag(outcome == BattleOutcome::Victory),
+        );
+        wander.variables.set(
+            "battle_outcome",
+            GameValue::Text(outcome.name().to_string()),
+        );
+        let message = match outcome {
+            BattleOutcome::Victory => "You are victorious!",
+            BattleOutcome::Defeat => "Your party has been defeated.",
+            BattleOutcome::Fled => "You escaped from the battle.",
+        };
+        log.add_line(message, DEFAULT_TEXT_COLOR);
+
+        // Party combatants come first, in party order
+        for (chr, combatant) in wander.party.iter_mut().zip(battle.combatants.iter()) {
+            chr.hp = i32::max(0, combatant.hp);
+        }
+
+        let (xp, gold) = battle.rewards();
+        wander.variables.set("battle_xp", GameValue::Number(xp));
+        if outcome == BattleOutcome::Victory {
+            wander.variables.add("gold", gold);
+            log.add_line(
+                &format!("You gain {} experience and {} gold.", xp, gold),
+                DEFAULT_TEXT_COLOR,
+            );
+            let survivors = wander.party.iter().filter(|c| c.is_alive()).count() as i32;
+            if survivors > 0 {
+                wander
+                    .party
+                    .iter_mut()
+                    .filter(|c| c.is_alive())
+                    .for_each(|c| c.xp += xp / survivors);
+            }
+        }
+        state
+            .set(AppState::MapWander)
+            .expect("Failed to change mode");
+    }
+}
+
+pub fn exit_battle(mut commands: Commands, cleanup: Query<(Entity, &BattleComponent)>) {
+    cleanup
+        .iter()
+        .for_each(|(e, _)| commands.entity(e).despawn());
+    commands.remove_resource::<BattleResource>();
 }
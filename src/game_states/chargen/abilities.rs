@@ -0,0 +1,154 @@
+use bracket_random::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Abilities {
+    pub strength: i32,
+    pub intelligence: i32,
+    pub wisdom: i32,
+    pub dexterity: i32,
+    pub constitution: i32,
+    pub charisma: i32,
+}
+
+impl Abilities {
+    pub fn average() -> Self {
+        Self {
+            strength: 10,
+            intelligence: 10,
+            wisdom: 10,
+            dexterity: 10,
+            constitution: 10,
+            charisma: 10,
+        }
+    }
+
+    /// Rolls each ability as 4d6, dropping the lowest die.
+    pub fn roll(rng: &mut RandomNumberGenerator) -> Self {
+        let mut roll = || {
+            let mut dice: Vec<i32> = (0..4).map(|_| rng.roll_dice(1, 6)).collect();
+            dice.sort_unstable();
+            dice.iter().skip(1).sum::<i32>()
+        };
+        Self {
+            strength: roll(),
+            intelligence: roll(),
+            wisdom: roll(),
+            dexterity: roll(),
+            constitution: roll(),
+            charisma: roll(),
+        }
+    }
+
+    /// Abilities after racial adjustments are applied.
+    pub fn with_race(&self, race: Race) -> Self {
+        let (st, int, wis, dex, con, cha) = race.adjustments();
+        Self {
+            strength: self.strength + st,
+            intelligence: self.intelligence + int,
+            wisdom: self.wisdom + wis,
+            dexterity: self.dexterity + dex,
+            constitution: self.constitution + con,
+            charisma: self.charisma + cha,
+        }
+    }
+
+    pub fn list(&self) -> [(&'static str, i32); 6] {
+        [
+            ("Strength", self.strength),
+            ("Intelligence", self.intelligence),
+            ("Wisdom", self.wisdom),
+            ("Dexterity", self.dexterity),
+            ("Constitution", self.constitution),
+            ("Charisma", self.charisma),
+        ]
+    }
+}
+
+/// The bonus (or penalty) an ability score gives: +1 for every two points
+/// above 10.
+pub fn ability_modifier(score: i32) -> i32 {
+    (score - 10).div_euclid(2)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Race {
+    Human,
+    Elf,
+    Dwarf,
+    Halfling,
+}
+
+impl Race {
+    pub const ALL: [Race; 4] = [Race::Human, Race::Elf, Race::Dwarf, Race::Halfling];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Race::Human => "Human",
+            Race::Elf => "Elf",
+            Race::Dwarf => "Dwarf",
+            Race::Halfling => "Halfling",
+        }
+    }
+
+    /// Str, Int, Wis, Dex, Con, Cha
+    fn adjustments(&self) -> (i32, i32, i32, i32, i32, i32) {
+        match self {
+            Race::Human => (0, 0, 0, 0, 0, 0),
+            Race::Elf => (0, 1, 0, 1, -1, 0),
+            Race::Dwarf => (1, 0, 0, 0, 1, -1),
+            Race::Halfling => (-1, 0, 0, 2, 0, 0),
+        }
+    }
+
+    /// Battle tiles per turn
+    pub fn movement(&self) -> i32 {
+        match self {
+            Race::Human | Race::Elf => 6,
+            Race::Dwarf | Race::Halfling => 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CharacterClass {
+    Fighter,
+    Cleric,
+    Thief,
+    MagicUser,
+}
+
+impl CharacterClass {
+    pub const ALL: [CharacterClass; 4] = [
+        CharacterClass::Fighter,
+        CharacterClass::Cleric,
+        CharacterClass::Thief,
+        CharacterClass::MagicUser,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharacterClass::Fighter => "Fighter",
+            CharacterClass::Cleric => "Cleric",
+            CharacterClass::Thief => "Thief",
+            CharacterClass::MagicUser => "Magic-User",
+        }
+    }
+
+    pub fn hit_die(&self) -> i32 {
+        match self {
+            CharacterClass::Fighter => 10,
+            CharacterClass::Cleric => 8,
+            CharacterClass::Thief => 6,
+            CharacterClass::MagicUser => 4,
+        }
+    }
+
+    pub fn base_to_hit(&self) -> i32 {
+        match self {
+            CharacterClass::Fighter => 2,
+            CharacterClass::Cleric | CharacterClass::Thief => 1,
+            CharacterClass::MagicUser => 0,
+        }
+    }
+}
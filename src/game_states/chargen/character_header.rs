@@ -1,24 +1,107 @@
+use super::{ability_modifier, Abilities, CharacterClass, Race};
+use crate::module::EquipSlot;
 use anyhow::Result;
 use ron::ser::{to_string_pretty, PrettyConfig};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CHARACTER_DIR: &str = "characters";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CharacterHeader {
     pub name: String,
+    pub race: Race,
+    pub class: CharacterClass,
+    /// Rolled abilities, before racial adjustments
+    pub abilities: Abilities,
+    pub level: i32,
+    pub xp: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+    /// Image file (relative to the assets directory)
+    pub portrait: Option<String>,
+    /// Item tags, from the module being played
+    #[serde(default)]
+    pub equipment: HashMap<EquipSlot, String>,
 }
 
 impl CharacterHeader {
     pub fn new() -> Self {
-        Self {
+        let mut chr = Self {
             name: "New Character".to_string(),
+            race: Race::Human,
+            class: CharacterClass::Fighter,
+            abilities: Abilities::average(),
+            level: 1,
+            xp: 0,
+            hp: 0,
+            max_hp: 0,
+            portrait: None,
+            equipment: HashMap::new(),
+        };
+        chr.reset_hit_points();
+        chr
+    }
+
+    pub fn effective_abilities(&self) -> Abilities {
+        self.abilities.with_race(self.race)
+    }
+
+    /// First level characters start with a full hit die.
+    pub fn reset_hit_points(&mut self) {
+        let con = ability_modifier(self.effective_abilities().constitution);
+        self.max_hp = i32::max(1, self.class.hit_die() + con);
+        self.hp = self.max_hp;
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    pub fn status(&self) -> &'static str {
+        if !self.is_alive() {
+            "Dead"
+        } else if self.hp * 2 < self.max_hp {
+            "Wounded"
+        } else {
+            "Healthy"
         }
     }
 
+    /// Unarmored armor class
+    pub fn armor_class(&self) -> i32 {
+        10 + ability_modifier(self.effective_abilities().dexterity)
+    }
+
+    pub fn to_hit(&self) -> i32 {
+        self.class.base_to_hit() + ability_modifier(self.effective_abilities().strength)
+    }
+
+    pub fn initiative_bonus(&self) -> i32 {
+        ability_modifier(self.effective_abilities().dexterity)
+    }
+
+    pub fn movement(&self) -> i32 {
+        self.race.movement()
+    }
+
+    pub fn filename(&self) -> PathBuf {
+        Path::new(CHARACTER_DIR).join(format!("{}.chr", self.name))
+    }
+
     pub fn save(&self) -> Result<()> {
-        let path = Path::new("characters").join(&self.name).join(".chr");
+        fs::create_dir_all(CHARACTER_DIR)?;
         let header_ron = to_string_pretty(&self, PrettyConfig::new())?;
-        std::fs::write(path, header_ron)?;
+        std::fs::write(self.filename(), header_ron)?;
+        Ok(())
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        fs::remove_file(self.filename())?;
         Ok(())
     }
 
@@ -28,21 +111,23 @@ impl CharacterHeader {
     }
 
     pub fn scan_available() -> Vec<Self> {
-        let paths = fs::read_dir("./characters").unwrap();
-        paths
+        let paths = match fs::read_dir(CHARACTER_DIR) {
+            Ok(paths) => paths,
+            Err(_) => return Vec::new(),
+        };
+        let mut characters: Vec<Self> = paths
             .flatten()
-            .flat_map(|path| {
-                if !path.path().is_dir() {
-                    let chr = CharacterHeader::load(&path.path());
-                    if chr.is_err() {
-                        None
-                    } else {
-                        Some(chr.unwrap())
-                    }
-                } else {
+            .map(|path| path.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "chr"))
+            .flat_map(|path| match CharacterHeader::load(&path) {
+                Ok(chr) => Some(chr),
+                Err(e) => {
+                    println!("Unable to load character {:?}: {:?}", path, e);
                     None
                 }
             })
-            .collect()
+            .collect();
+        characters.sort_by(|a, b| a.name.cmp(&b.name));
+        characters
     }
 }
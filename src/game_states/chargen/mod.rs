@@ -1,2 +1,209 @@
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Color32, Pos2, TextureId},
+    EguiContext,
+};
+use bracket_random::prelude::RandomNumberGenerator;
+use std::fs;
+mod abilities;
+pub use abilities::*;
 mod character_header;
 pub use character_header::*;
+
+const PORTRAIT_DIR: &str = "portraits";
+const PORTRAIT_TEXTURE_BASE: u64 = 1000;
+
+pub struct ChargenResource {
+    character: CharacterHeader,
+    rng: RandomNumberGenerator,
+    /// Portrait image files, relative to the assets directory
+    portraits: Vec<String>,
+    portrait_idx: usize,
+    error: Option<String>,
+}
+
+pub fn resume_chargen(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut portraits: Vec<String> = fs::read_dir(format!("assets/{}", PORTRAIT_DIR))
+        .map(|paths| {
+            paths
+                .flatten()
+                .filter(|p| p.path().is_file())
+                .map(|p| format!("{}/{}", PORTRAIT_DIR, p.file_name().to_string_lossy()))
+                .collect()
+        })
+        .unwrap_or_default();
+    portraits.sort();
+    for (i, file) in portraits.iter().enumerate() {
+        egui_context.set_egui_texture(
+            PORTRAIT_TEXTURE_BASE + i as u64,
+            asset_server.load(file.as_str()),
+        );
+    }
+
+    let mut rng = RandomNumberGenerator::new();
+    let mut character = CharacterHeader::new();
+    character.abilities = Abilities::roll(&mut rng);
+    character.portrait = portraits.first().cloned();
+    character.reset_hit_points();
+
+    commands.insert_resource(ChargenResource {
+        character,
+        rng,
+        portraits,
+        portrait_idx: 0,
+        error: None,
+    });
+}
+
+pub fn chargen(
+    egui_context: ResMut<EguiContext>,
+    mut chargen: ResMut<ChargenResource>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let mut finished = false;
+
+    egui::Window::new("Create a Character")
+        .resizable(false)
+        .title_bar(true)
+        .fixed_pos(Pos2::new(200.0, 100.0))
+        .fixed_size(egui::Vec2::new(800.0, 600.0))
+        .show(egui_context.ctx(), |ui| {
+            let chargen = &mut *chargen;
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut chargen.character.name);
+            });
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    let mut race = chargen.character.race;
+                    egui::ComboBox::from_label("Race")
+                        .selected_text(race.name())
+                        .show_ui(ui, |ui| {
+                            for r in Race::ALL.iter() {
+                                ui.selectable_value(&mut race, *r, r.name());
+                            }
+                        });
+                    let mut class = chargen.character.class;
+                    egui::ComboBox::from_label("Class")
+                        .selected_text(class.name())
+                        .show_ui(ui, |ui| {
+                            for c in CharacterClass::ALL.iter() {
+                                ui.selectable_value(&mut class, *c, c.name());
+                            }
+                        });
+                    if race != chargen.character.race || class != chargen.character.class {
+                        chargen.character.race = race;
+                        chargen.character.class = class;
+                        chargen.character.reset_hit_points();
+                    }
+                    ui.separator();
+
+                    let abilities = chargen.character.effective_abilities();
+                    for (name, score) in abilities.list().iter() {
+                        ui.label(format!(
+                            "{}: {} ({:+})",
+                            name,
+                            score,
+                            ability_modifier(*score)
+                        ));
+                    }
+                    if ui.button("Roll Abilities").clicked() {
+                        chargen.character.abilities = Abilities::roll(&mut chargen.rng);
+                        chargen.character.reset_hit_points();
+                    }
+                    ui.separator();
+
+                    let chr = &chargen.character;
+                    ui.colored_label(Color32::LIGHT_GREEN, format!("Hit Points: {}", chr.max_hp));
+                    ui.colored_label(
+                        Color32::LIGHT_GREEN,
+                        format!("Armor Class: {}", chr.armor_class()),
+                    );
+                    ui.colored_label(Color32::LIGHT_GREEN, format!("To Hit: {:+}", chr.to_hit()));
+                    ui.colored_label(
+                        Color32::LIGHT_GREEN,
+                        format!("Initiative: {:+}", chr.initiative_bonus()),
+                    );
+                    ui.colored_label(
+                        Color32::LIGHT_GREEN,
+                        format!("Movement: {}", chr.movement()),
+                    );
+                });
+
+                ui.vertical(|ui| {
+                    ui.label("Portrait");
+                    if chargen.portraits.is_empty() {
+                        ui.colored_label(Color32::RED, "No portraits found in assets/portraits.");
+                    } else {
+                        ui.image(
+                            TextureId::User(PORTRAIT_TEXTURE_BASE + chargen.portrait_idx as u64),
+                            egui::Vec2::new(200.0, 200.0),
+                        );
+                        ui.horizontal(|ui| {
+                            let count = chargen.portraits.len();
+                            if ui.button("<").clicked() {
+                                chargen.portrait_idx = (chargen.portrait_idx + count - 1) % count;
+                            }
+                            if ui.button(">").clicked() {
+                                chargen.portrait_idx = (chargen.portrait_idx + 1) % count;
+                            }
+                        });
+                        chargen.character.portrait =
+                            Some(chargen.portraits[chargen.portrait_idx].clone());
+                    }
+                });
+            });
+            ui.separator();
+
+            if let Some(error) = &chargen.error {
+                ui.colored_label(Color32::RED, error);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Save Character").clicked() {
+                    let name = chargen.character.name.trim().to_string();
+                    if name.is_empty() || name.contains(|c: char| c == '/' || c == '\\') {
+                        chargen.error = Some("Please enter a valid name.".to_string());
+                    } else if CharacterHeader::scan_available()
+                        .iter()
+                        .any(|c| c.name == name)
+                    {
+                        chargen.error =
+                            Some("A character with that name already exists.".to_string());
+                    } else {
+                        chargen.character.name = name;
+                        match chargen.character.save() {
+                            Ok(_) => finished = true,
+                            Err(e) => chargen.error = Some(format!("Unable to save: {:?}", e)),
+                        }
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    finished = true;
+                }
+            });
+        });
+
+    if finished {
+        state
+            .set(AppState::MainMenu)
+            .expect("Failed to change mode");
+    }
+}
+
+pub fn exit_chargen(
+    mut commands: Commands,
+    mut egui_context: ResMut<EguiContext>,
+    chargen: Res<ChargenResource>,
+) {
+    for i in 0..chargen.portraits.len() {
+        egui_context.remove_egui_texture(PORTRAIT_TEXTURE_BASE + i as u64);
+    }
+    commands.remove_resource::<ChargenResource>();
+}
@@ -10,7 +10,7 @@ use bevy_egui::{
     EguiContext,
 };
 
-use super::CharacterHeader;
+use super::{save_game::SaveGame, CharacterHeader};
 
 #[derive(Component)]
 pub struct MainMenuUi;
@@ -20,16 +20,19 @@ pub struct AvailableModules {
     characters: Vec<CharacterHeader>,
 }
 
+pub const MAX_PARTY_SIZE: usize = 6;
+
 pub struct ModuleSelector {
     pub module: Option<Module>,
-    pub party: Vec<usize>,
+    pub party: Vec<CharacterHeader>,
+    pub save: Option<SaveGame>,
 }
 
 pub fn main_menu(
     egui_context: ResMut<EguiContext>,
     mut app_exit_events: ResMut<Events<bevy::app::AppExit>>,
     mut state: ResMut<State<AppState>>,
-    available_modules: Res<AvailableModules>,
+    mut available_modules: ResMut<AvailableModules>,
     mut selected_module: ResMut<ModuleSelector>,
 ) {
     egui::Window::new("Welcome to Pyrite Box")
@@ -45,7 +48,7 @@ pub fn main_menu(
                 ui.colored_label(Color32::LIGHT_GREEN, &module.description);
                 ui.colored_label(Color32::GREEN, format!("Author: {}", &module.author));
                 ui.horizontal(|ui| {
-                    if selected_module.party.is_empty() && ui.button("Play").clicked() {
+                    if !selected_module.party.is_empty() && ui.button("Play").clicked() {
                         selected_module.module = Some(
                             crate::modules::load_module(module.filename.as_ref().unwrap()).unwrap(),
                         );
@@ -53,6 +56,23 @@ pub fn main_menu(
                             .set(AppState::MapWanderLoader)
                             .expect("Failed to change mode");
                     }
+                    if SaveGame::exists(module.filename.as_ref().unwrap())
+                        && ui.button("Load Saved Game").clicked()
+                    {
+                        match SaveGame::load(module.filename.as_ref().unwrap()) {
+                            Ok(save) => {
+                                selected_module.module = Some(
+                                    crate::modules::load_module(module.filename.as_ref().unwrap())
+                                        .unwrap(),
+                                );
+                                selected_module.save = Some(save);
+                                state
+                                    .set(AppState::MapWanderLoader)
+                                    .expect("Failed to change mode");
+                            }
+                            Err(e) => println!("Unable to load game: {:?}", e),
+                        }
+                    }
                     if ui.button("Edit").clicked() {
                         selected_module.module = Some(
                             crate::modules::load_module(module.filename.as_ref().unwrap()).unwrap(),
@@ -91,8 +111,18 @@ pub fn main_menu(
                     ui.colored_label(Color32::WHITE, "Your Party");
                     if selected_module.party.is_empty() {
                         ui.colored_label(Color32::RED, "Please add 1-6 characters to your party.");
-                    } else {
-                        // List party
+                    }
+                    let mut removed = None;
+                    for (i, chr) in selected_module.party.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(Color32::LIGHT_BLUE, character_summary(chr));
+                            if ui.button("Remove").clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = removed {
+                        selected_module.party.remove(i);
                     }
                 });
                 ui.vertical(|ui| {
@@ -100,18 +130,52 @@ pub fn main_menu(
                     if available_modules.characters.is_empty() {
                         ui.colored_label(Color32::RED, "There are no available characters.");
                     }
+                    let mut deleted = None;
+                    for (i, chr) in available_modules.characters.iter().enumerate() {
+                        let in_party = selected_module.party.iter().any(|p| p.name == chr.name);
+                        ui.horizontal(|ui| {
+                            ui.colored_label(Color32::LIGHT_GREEN, character_summary(chr));
+                            if !in_party
+                                && selected_module.party.len() < MAX_PARTY_SIZE
+                                && ui.button("Add").clicked()
+                            {
+                                selected_module.party.push(chr.clone());
+                            }
+                            if ui.button("Delete").clicked() {
+                                deleted = Some(i);
+                            }
+                        });
+                    }
+                    if let Some(i) = deleted {
+                        match available_modules.characters[i].delete() {
+                            Ok(_) => {
+                                let chr = available_modules.characters.remove(i);
+                                selected_module.party.retain(|p| p.name != chr.name);
+                            }
+                            Err(e) => println!("Unable to delete character: {:?}", e),
+                        }
+                    }
 
-                    /*
-                    ui.button("<");
-                    ui.button(">");
-                    ui.button("Create New Character");
-                    ui.button("Delete Character");
-                    */
+                    if ui.button("Create New Character").clicked() {
+                        state
+                            .set(AppState::CharacterGeneration)
+                            .expect("Failed to change mode");
+                    }
                 });
             });
         });
 }
 
+fn character_summary(chr: &CharacterHeader) -> String {
+    format!(
+        "{}, level {} {} {}",
+        chr.name,
+        chr.level,
+        chr.race.name(),
+        chr.class.name()
+    )
+}
+
 pub fn resume_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn_bundle(OrthographicCameraBundle::new_2d())
@@ -131,6 +195,7 @@ pub fn resume_main_menu(mut commands: Commands, asset_server: Res<AssetServer>)
     commands.insert_resource(ModuleSelector {
         module: None,
         party: Vec::new(),
+        save: None,
     });
 }
 
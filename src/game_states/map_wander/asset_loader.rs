@@ -30,7 +30,11 @@ pub fn start_asset_loader(
     let module = startup.module.as_ref().unwrap().clone();
 
     // Determine player starting location
-    let map_idx = module.starting_map_idx;
+    let map_idx = if let Some(save) = &startup.save {
+        save.map_idx
+    } else {
+        module.starting_map_idx
+    };
 
     // Initiate the asset loading process.
     // This is async, so we have to track it. If we don't wait for
@@ -0,0 +1,66 @@
+use super::{player_movement::MoveOccurred, WanderResource, WanderingPlayer};
+use crate::region::region_map::{map_editor::automap_ui, ExploredMap};
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Pos2},
+    EguiContext,
+};
+
+/// Records what the party can see whenever it moves, or arrives on a map
+/// it has no record of.
+pub fn update_automap(
+    mut move_occurred: EventReader<MoveOccurred>,
+    player_query: Query<&WanderingPlayer>,
+    mut wander: ResMut<WanderResource>,
+) {
+    let moved = move_occurred.iter().count() > 0;
+    let map_idx = wander.map_idx;
+    if !moved && wander.explored.contains_key(&map_idx) {
+        return;
+    }
+
+    let wander = &mut *wander;
+    let map = &wander.module.maps[&map_idx];
+    let explored = wander
+        .explored
+        .entry(map_idx)
+        .or_insert_with(|| ExploredMap::new(map));
+    player_query.iter().for_each(|wp| {
+        explored.reveal(map, wp.x, wp.y, wp.facing);
+    });
+}
+
+/// M toggles a map of everywhere the party has been.
+pub fn display_automap(
+    keyboard_input: Res<Input<KeyCode>>,
+    egui_context: ResMut<EguiContext>,
+    player_query: Query<&WanderingPlayer>,
+    mut wander: ResMut<WanderResource>,
+) {
+    if keyboard_input.just_pressed(KeyCode::M) && !egui_context.ctx().wants_keyboard_input() {
+        wander.show_automap = !wander.show_automap;
+    }
+    if !wander.show_automap {
+        return;
+    }
+
+    let map = &wander.module.maps[&wander.map_idx];
+    let explored = match wander.explored.get(&wander.map_idx) {
+        Some(explored) => explored,
+        None => return,
+    };
+    player_query.iter().for_each(|wp| {
+        egui::Window::new(format!("Automap: {}", map.name))
+            .default_pos(Pos2::new(25.0, 60.0))
+            .default_size(egui::Vec2::new(300.0, 300.0))
+            .show(egui_context.ctx(), |ui| {
+                automap_ui(
+                    ui,
+                    map,
+                    explored,
+                    (wp.x, wp.y, wp.facing),
+                    &wander.module.materials,
+                );
+            });
+    });
+}
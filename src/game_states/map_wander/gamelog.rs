@@ -36,6 +36,27 @@ impl GameLog {
     pub fn clear(&mut self) {
         self.buffer.clear();
     }
+
+    /// Lists the log contents as text and RGB color, for saving.
+    pub fn lines(&self) -> Vec<(String, (u8, u8, u8))> {
+        self.buffer
+            .iter()
+            .map(|e| (e.text.clone(), (e.color.r(), e.color.g(), e.color.b())))
+            .collect()
+    }
+
+    /// Replaces the log contents with already-revealed lines.
+    pub fn restore(&mut self, lines: &[(String, (u8, u8, u8))]) {
+        self.buffer = lines
+            .iter()
+            .map(|(text, color)| GameLogEntry {
+                revealed: true,
+                progress: text.len(),
+                text: text.clone(),
+                color: Color32::from_rgb(color.0, color.1, color.2),
+            })
+            .collect();
+    }
 }
 
 struct GameLogEntry {
@@ -0,0 +1,186 @@
+use super::{
+    gamelog::{GameLog, DEFAULT_TEXT_COLOR},
+    CharacterHeader, WanderResource,
+};
+use crate::module::{EquipSlot, ItemKind};
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Color32, Pos2},
+    EguiContext,
+};
+use bracket_random::prelude::RandomNumberGenerator;
+
+/// True if the item is in the pack or equipped by anyone in the party.
+pub fn party_has_item(inventory: &[String], party: &[CharacterHeader], tag: &str) -> bool {
+    inventory.iter().any(|i| i == tag)
+        || party.iter().any(|c| c.equipment.values().any(|i| i == tag))
+}
+
+impl WanderResource {
+    pub fn has_item(&self, tag: &str) -> bool {
+        party_has_item(&self.inventory, &self.party, tag)
+    }
+
+    pub fn give_item(&mut self, tag: &str) {
+        self.inventory.push(tag.to_string());
+    }
+
+    /// Removes one copy of an item, preferring the pack over equipped
+    /// items. Returns false if the party doesn't have it.
+    pub fn take_item(&mut self, tag: &str) -> bool {
+        if let Some(idx) = self.inventory.iter().position(|i| i == tag) {
+            self.inventory.remove(idx);
+            return true;
+        }
+        for chr in self.party.iter_mut() {
+            let slot = chr
+                .equipment
+                .iter()
+                .find(|(_, i)| *i == tag)
+                .map(|(slot, _)| *slot);
+            if let Some(slot) = slot {
+                chr.equipment.remove(&slot);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves an item from the pack to a character, swapping out whatever
+    /// was in that slot.
+    fn equip(&mut self, character: usize, inventory_idx: usize) {
+        let tag = self.inventory[inventory_idx].clone();
+        if let Some(slot) = self.module.items.get(&tag).and_then(|item| item.slot()) {
+            self.inventory.remove(inventory_idx);
+            if let Some(old) = self.party[character].equipment.insert(slot, tag) {
+                self.inventory.push(old);
+            }
+        }
+    }
+
+    fn unequip(&mut self, character: usize, slot: EquipSlot) {
+        if let Some(tag) = self.party[character].equipment.remove(&slot) {
+            self.inventory.push(tag);
+        }
+    }
+
+    /// Uses up a consumable on a character, returning what happened.
+    fn use_item(&mut self, character: usize, inventory_idx: usize) -> Option<String> {
+        let tag = self.inventory[inventory_idx].clone();
+        let heal = match self.module.items.get(&tag).map(|item| &item.kind) {
+            Some(ItemKind::Consumable { heal }) => *heal,
+            _ => return None,
+        };
+        let name = self.module.item_name(&tag);
+        let chr = &mut self.party[character];
+        if !chr.is_alive() {
+            return Some(format!("{} is beyond the help of {}.", chr.name, name));
+        }
+        let amount = i32::max(0, heal.roll(&mut RandomNumberGenerator::new()));
+        chr.hp = i32::min(chr.max_hp, chr.hp + amount);
+        let message = format!(
+            "{} uses {} and recovers {} hit points.",
+            chr.name, name, amount
+        );
+        self.inventory.remove(inventory_idx);
+        Some(message)
+    }
+}
+
+enum InventoryAction {
+    Equip(usize),
+    Unequip(EquipSlot),
+    Use(usize),
+}
+
+/// The party's pack and the selected character's equipment. Toggled with I.
+pub fn display_inventory(
+    egui_context: ResMut<EguiContext>,
+    mut wander: ResMut<WanderResource>,
+    mut log: ResMut<GameLog>,
+) {
+    if !wander.show_inventory || wander.party.is_empty() {
+        return;
+    }
+    if wander.inventory_character >= wander.party.len() {
+        wander.inventory_character = 0;
+    }
+
+    let mut action = None;
+    let mut selected = wander.inventory_character;
+    egui::Window::new("Inventory")
+        .resizable(false)
+        .title_bar(true)
+        .fixed_pos(Pos2::new(200.0, 60.0))
+        .fixed_size(egui::Vec2::new(600.0, 400.0))
+        .show(egui_context.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                for (i, chr) in wander.party.iter().enumerate() {
+                    ui.selectable_value(&mut selected, i, &chr.name);
+                }
+            });
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.colored_label(Color32::WHITE, "Equipped");
+                    let chr = &wander.party[selected];
+                    for slot in EquipSlot::ALL.iter() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}:", slot.name()));
+                            if let Some(tag) = chr.equipment.get(slot) {
+                                ui.colored_label(
+                                    Color32::LIGHT_GREEN,
+                                    wander.module.item_name(tag),
+                                );
+                                if ui.button("Remove").clicked() {
+                                    action = Some(InventoryAction::Unequip(*slot));
+                                }
+                            } else {
+                                ui.colored_label(Color32::DARK_GRAY, "Nothing");
+                            }
+                        });
+                    }
+                });
+
+                ui.vertical(|ui| {
+                    ui.colored_label(Color32::WHITE, "Pack");
+                    if wander.inventory.is_empty() {
+                        ui.colored_label(Color32::DARK_GRAY, "The pack is empty.");
+                    }
+                    for (i, tag) in wander.inventory.iter().enumerate() {
+                        let item = wander.module.items.get(tag);
+                        ui.horizontal(|ui| {
+                            let label = ui
+                                .colored_label(Color32::LIGHT_GREEN, wander.module.item_name(tag));
+                            if let Some(item) = item {
+                                if !item.description.is_empty() {
+                                    label.on_hover_text(&item.description);
+                                }
+                                if item.slot().is_some() && ui.button("Equip").clicked() {
+                                    action = Some(InventoryAction::Equip(i));
+                                }
+                                if matches!(item.kind, ItemKind::Consumable { .. })
+                                    && ui.button("Use").clicked()
+                                {
+                                    action = Some(InventoryAction::Use(i));
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+        });
+
+    wander.inventory_character = selected;
+    match action {
+        Some(InventoryAction::Equip(idx)) => wander.equip(selected, idx),
+        Some(InventoryAction::Unequip(slot)) => wander.unequip(selected, slot),
+        Some(InventoryAction::Use(idx)) => {
+            if let Some(message) = wander.use_item(selected, idx) {
+                log.add_line(&message, DEFAULT_TEXT_COLOR);
+            }
+        }
+        None => {}
+    }
+}
@@ -1,19 +1,28 @@
 use self::player_movement::PlayerMoveRequest;
-use super::ModuleSelector;
+use self::save_game::PendingLoad;
+use super::{CharacterHeader, ModuleSelector};
+use crate::module::game_events::GameVariables;
 use crate::module::game_events::InputChoice;
 use crate::module::game_events::TriggerEvent;
 use crate::module::Direction;
 use crate::module::Module;
-use crate::region::region_map::map_editor::{MapEditor, MapEditorSettings};
+use crate::region::region_map::map_editor::{EditHistory, MapEditor, MapEditorSettings};
+use crate::region::region_map::ExploredMap;
 use crate::region::{region_assets::RegionAssets, region_map::geometry::GEOMETRY_SIZE};
 use bevy::{prelude::*, render::camera::PerspectiveProjection};
 use bevy_egui::{
     egui::{Pos2, Window},
     EguiContext,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 pub mod asset_loader;
+pub mod automap;
 pub mod gamelog;
+pub mod inventory;
+pub mod party_roster;
 pub mod player_movement;
+pub mod save_game;
 pub mod sprites;
 use bevy_egui::egui;
 
@@ -37,11 +46,24 @@ pub struct WanderResource {
     pub module: Module,
     pub map_idx: usize,
     pub editor_settings: MapEditorSettings,
+    pub editor_history: EditHistory,
     pub show_editor: bool,
     pub allow_movement: bool,
     pub script_input: Option<WanderInput>,
+    pub variables: GameVariables,
+    /// Encounter tag for the battle that is about to start
+    pub battle_encounter: Option<String>,
+    pub party: Vec<CharacterHeader>,
+    /// Item tags carried by the party but not equipped
+    pub inventory: Vec<String>,
+    pub show_inventory: bool,
+    pub inventory_character: usize,
+    /// What the party has seen of each map, by map index
+    pub explored: HashMap<usize, ExploredMap>,
+    pub show_automap: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct WanderInput {
     pub title: String,
     pub message: String,
@@ -98,6 +120,9 @@ pub fn map_wander(
         if keyboard_input.just_pressed(KeyCode::E) {
             wander.show_editor = !wander.show_editor;
         }
+        if keyboard_input.just_pressed(KeyCode::I) {
+            wander.show_inventory = !wander.show_inventory;
+        }
 
         Window::new("Navigation")
             .auto_sized()
@@ -116,13 +141,16 @@ pub fn map_wander(
             let map_idx = wander.map_idx;
             let mut settings = wander.editor_settings.clone();
             settings.highlight_player = Some((wp.x, wp.y, wp.facing));
+            let mut history = std::mem::take(&mut wander.editor_history);
             MapEditor::render_in_module(
                 egui_context.ctx(),
                 &mut settings,
+                &mut history,
                 &mut wander.module,
                 map_idx,
             );
             wander.editor_settings = settings;
+            wander.editor_history = history;
         }
     });
 }
@@ -146,6 +174,7 @@ pub fn resume_map_wander(
     mut triggers: EventWriter<TriggerEvent>,
     assets: Res<RegionAssets>,
     wander: Option<ResMut<WanderResource>>,
+    player_query: Query<&WanderingPlayer>,
 ) {
     let module = startup.module.as_ref().unwrap().clone();
     let map_idx = module.starting_map_idx;
@@ -164,21 +193,37 @@ pub fn resume_map_wander(
             .insert(WanderGeometry {});
     }
 
-    if wander.is_some() {
+    if let Some(wander) = &wander {
         // We're resuming from another state
         // We need to generate the camera & light - the player already exists, so we
         // can query it for location information.
+        let map = &wander.module.maps[&wander.map_idx];
+        player_query.iter().for_each(|wp| {
+            let (x, y) = map.tile_location(wp.x as f32, wp.y as f32);
+            spawn_light_and_camera(
+                &mut commands,
+                (x + 0.5) * GEOMETRY_SIZE,
+                (y + 0.5) * GEOMETRY_SIZE,
+                0.5 * GEOMETRY_SIZE,
+                wp.facing,
+            );
+        });
     } else {
         // New game
         let (start_x, start_y, start_z, facing, tile_x, tile_y) =
             get_starting_position(&module, map_idx);
 
-        // Wander player - start by running module/map initialization events
-        if !module.module_start_event.is_empty() {
-            triggers.send(TriggerEvent(module.module_start_event.clone()));
-        }
-        if !module.maps[&map_idx].map_start_event.is_empty() {
-            triggers.send(TriggerEvent(module.maps[&map_idx].map_start_event.clone()));
+        if let Some(save) = &startup.save {
+            // Restoring a saved game - the module start events have already run
+            commands.insert_resource(PendingLoad(save.clone()));
+        } else {
+            // Wander player - start by running module/map initialization events
+            if !module.module_start_event.is_empty() {
+                triggers.send(TriggerEvent(module.module_start_event.clone()));
+            }
+            if !module.maps[&map_idx].map_start_event.is_empty() {
+                triggers.send(TriggerEvent(module.maps[&map_idx].map_start_event.clone()));
+            }
         }
 
         // Resource
@@ -186,51 +231,21 @@ pub fn resume_map_wander(
             map_idx,
             module,
             editor_settings: MapEditorSettings::default(),
+            editor_history: EditHistory::default(),
             show_editor: false,
             allow_movement: true,
             script_input: None,
+            variables: GameVariables::new(),
+            battle_encounter: None,
+            party: startup.party.clone(),
+            inventory: Vec::new(),
+            show_inventory: false,
+            inventory_character: 0,
+            explored: HashMap::new(),
+            show_automap: false,
         });
 
-        // Entity for the light and camera
-
-        // light
-        commands
-            .spawn_bundle(PointLightBundle {
-                point_light: PointLight {
-                    color: Color::rgb(1.0, 1.0, 1.0),
-                    // depth: 0.1..100.0,
-                    // fov: f32::to_radians(60.0),
-                    intensity: 1600.0,
-                    range: 100.0,
-                    //radius: f32::to_radians(360.0),
-                    shadows_enabled: false,
-                    ..Default::default()
-                },
-                transform: Transform::from_xyz(start_x, start_y, start_z),
-                ..Default::default()
-            })
-            .insert(MapWander {})
-            .insert(WanderLight {});
-
-        // camera
-        let perspective = PerspectiveProjection {
-            fov: std::f32::consts::FRAC_PI_2, //1.5708,
-            aspect_ratio: 1280.0 / 1024.0,
-            near: 0.1,
-            far: 1000.0,
-        };
-
-        commands
-            .spawn_bundle(PerspectiveCameraBundle {
-                perspective_projection: perspective,
-                transform: Transform::from_xyz(start_x, start_y, start_z).looking_at(
-                    facing.camera_look_at(&Vec3::new(start_x, start_y, start_z)),
-                    Vec3::new(0.0, 0.0, 1.0),
-                ),
-                ..Default::default()
-            })
-            .insert(MapWander {})
-            .insert(WanderCamera {});
+        spawn_light_and_camera(&mut commands, start_x, start_y, start_z, facing);
 
         // Setup the player
         commands.spawn().insert(WanderingPlayer {
@@ -241,6 +256,48 @@ pub fn resume_map_wander(
     }
 }
 
+/// Spawns the point light and perspective camera that follow the player
+fn spawn_light_and_camera(commands: &mut Commands, x: f32, y: f32, z: f32, facing: Direction) {
+    // light
+    commands
+        .spawn_bundle(PointLightBundle {
+            point_light: PointLight {
+                color: Color::rgb(1.0, 1.0, 1.0),
+                // depth: 0.1..100.0,
+                // fov: f32::to_radians(60.0),
+                intensity: 1600.0,
+                range: 100.0,
+                //radius: f32::to_radians(360.0),
+                shadows_enabled: false,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, z),
+            ..Default::default()
+        })
+        .insert(MapWander {})
+        .insert(WanderLight {});
+
+    // camera
+    let perspective = PerspectiveProjection {
+        fov: std::f32::consts::FRAC_PI_2, //1.5708,
+        aspect_ratio: 1280.0 / 1024.0,
+        near: 0.1,
+        far: 1000.0,
+    };
+
+    commands
+        .spawn_bundle(PerspectiveCameraBundle {
+            perspective_projection: perspective,
+            transform: Transform::from_xyz(x, y, z).looking_at(
+                facing.camera_look_at(&Vec3::new(x, y, z)),
+                Vec3::new(0.0, 0.0, 1.0),
+            ),
+            ..Default::default()
+        })
+        .insert(MapWander {})
+        .insert(WanderCamera {});
+}
+
 pub fn exit_map_wander(mut commands: Commands, cleanup: Query<(Entity, &MapWander)>) {
     cleanup
         .iter()
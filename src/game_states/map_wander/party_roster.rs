@@ -0,0 +1,64 @@
+use super::WanderResource;
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Color32, Pos2, TextureId},
+    EguiContext,
+};
+
+/// egui texture ids for party portraits start here, clear of the module's
+/// UI images.
+const PARTY_TEXTURE_BASE: u64 = 2000;
+const PORTRAIT_SIZE: f32 = 48.0;
+
+/// Shows each party member's portrait, hit points and status.
+pub fn display_party(
+    mut egui_context: ResMut<EguiContext>,
+    asset_server: Res<AssetServer>,
+    wander: Res<WanderResource>,
+    mut registered: Local<Vec<Option<String>>>,
+) {
+    // The party can change (e.g. loading a game), so re-register portraits
+    // whenever they differ from what egui knows about.
+    let portraits: Vec<Option<String>> = wander.party.iter().map(|c| c.portrait.clone()).collect();
+    if *registered != portraits {
+        for (i, portrait) in portraits.iter().enumerate() {
+            if let Some(file) = portrait {
+                egui_context.set_egui_texture(
+                    PARTY_TEXTURE_BASE + i as u64,
+                    asset_server.load(file.as_str()),
+                );
+            }
+        }
+        *registered = portraits;
+    }
+
+    egui::Window::new("Party")
+        .resizable(false)
+        .title_bar(true)
+        .fixed_pos(Pos2::new(1280.0 - 250.0, 60.0))
+        .fixed_size(egui::Vec2::new(230.0, 500.0))
+        .show(egui_context.ctx(), |ui| {
+            for (i, chr) in wander.party.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if chr.portrait.is_some() {
+                        ui.image(
+                            TextureId::User(PARTY_TEXTURE_BASE + i as u64),
+                            egui::Vec2::new(PORTRAIT_SIZE, PORTRAIT_SIZE),
+                        );
+                    }
+                    ui.vertical(|ui| {
+                        ui.colored_label(Color32::WHITE, &chr.name);
+                        let color = match chr.status() {
+                            "Dead" => Color32::RED,
+                            "Wounded" => Color32::YELLOW,
+                            _ => Color32::LIGHT_GREEN,
+                        };
+                        ui.colored_label(
+                            color,
+                            format!("HP: {}/{} ({})", chr.hp, chr.max_hp, chr.status()),
+                        );
+                    });
+                });
+            }
+        });
+}
@@ -1,8 +1,18 @@
-use super::{WanderCamera, WanderLight, WanderResource, WanderingPlayer};
-use crate::{module::game_events::TriggerEvent, region::region_map::geometry::GEOMETRY_SIZE};
+use super::{
+    gamelog::{GameLog, DEFAULT_TEXT_COLOR},
+    WanderCamera, WanderLight, WanderResource, WanderingPlayer,
+};
+use crate::{
+    module::game_events::TriggerEvent,
+    region::region_map::{geometry::GEOMETRY_SIZE, DoorState, RegionMap},
+};
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+const BUMP_MESSAGE: &str = "Ouch! You bump into a wall.";
+const LOCKED_MESSAGE: &str = "The door is locked.";
+const OPEN_MESSAGE: &str = "The door creaks open.";
+
 pub struct MoveOccurred;
 
 #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -24,6 +34,7 @@ pub fn player_move(
     mut wander: ResMut<WanderResource>,
     mut triggers: EventWriter<TriggerEvent>,
     mut move_occurred: EventWriter<MoveOccurred>,
+    mut log: ResMut<GameLog>,
 ) {
     let mut moved = false;
     let map_idx = wander.map_idx;
@@ -41,16 +52,26 @@ pub fn player_move(
                     moved = true;
                 }
                 PlayerMoveRequest::Backwards => {
-                    let (dx, dy) = wp.facing.delta_backward();
-                    wp.x += dx;
-                    wp.y += dy;
-                    moved = true;
+                    if wander.module.maps[&map_idx].can_move(wp.x, wp.y, wp.facing.opposite()) {
+                        let (dx, dy) = wp.facing.delta_backward();
+                        wp.x += dx;
+                        wp.y += dy;
+                        moved = true;
+                    } else {
+                        log.add_line(BUMP_MESSAGE, DEFAULT_TEXT_COLOR);
+                    }
                 }
                 PlayerMoveRequest::Forwards => {
-                    let (dx, dy) = wp.facing.delta_forward();
-                    wp.x += dx;
-                    wp.y += dy;
-                    moved = true;
+                    if wander.module.maps[&map_idx].can_move(wp.x, wp.y, wp.facing) {
+                        let (dx, dy) = wp.facing.delta_forward();
+                        wp.x += dx;
+                        wp.y += dy;
+                        moved = true;
+                    } else if wander.module.maps[&map_idx].is_door(wp.x, wp.y, wp.facing) {
+                        try_open_door(&mut wander, &wp, &mut log, &mut triggers);
+                    } else {
+                        log.add_line(BUMP_MESSAGE, DEFAULT_TEXT_COLOR);
+                    }
                 }
                 PlayerMoveRequest::ChangeMap { index, x, y } => {
                     wp.x = *x as i32;
@@ -63,8 +84,10 @@ pub fn player_move(
         }
 
         if moved {
-            wp.x = wp.x.clamp(0, wander.module.maps[&map_idx].size.0 as i32);
-            wp.y = wp.y.clamp(0, wander.module.maps[&map_idx].size.1 as i32);
+            wp.x =
+                wp.x.clamp(0, wander.module.maps[&map_idx].size.0 as i32 - 1);
+            wp.y =
+                wp.y.clamp(0, wander.module.maps[&map_idx].size.1 as i32 - 1);
             let new_location = (wander.module.maps[&map_idx].size.0 * wp.y as u32) + wp.x as u32;
             if let Some((direction, trigger)) =
                 &wander.module.maps[&map_idx].tiles[previous_location as usize].exit_trigger
@@ -80,18 +103,80 @@ pub fn player_move(
                     triggers.send(TriggerEvent(trigger.clone()));
                 }
             }
-            let (x, y) = wander.module.maps[&map_idx].tile_location(wp.x as f32, wp.y as f32);
+            let map = &wander.module.maps[&map_idx];
             move_set.q0().iter_mut().for_each(|(_, mut trans)| {
-                trans.translation.x = (x * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
-                trans.translation.y = (y * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
+                place_at_player(map, &wp, &mut trans, false);
             });
             move_set.q1().iter_mut().for_each(|(_, mut trans)| {
-                trans.translation.x = (x * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
-                trans.translation.y = (y * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
-                let target = wp.facing.camera_look_at(&trans.translation);
-                trans.look_at(target, Vec3::new(0.0, 0.0, 1.0));
+                place_at_player(map, &wp, &mut trans, true);
             });
             move_occurred.send(MoveOccurred {});
         }
     });
 }
+
+/// Walking into a closed door opens it. Locked doors need their key item
+/// to be somewhere in the party. Walking into an open door that still
+/// can't be passed bumps, like any other wall.
+fn try_open_door(
+    wander: &mut WanderResource,
+    wp: &WanderingPlayer,
+    log: &mut GameLog,
+    triggers: &mut EventWriter<TriggerEvent>,
+) {
+    let map_idx = wander.map_idx;
+    let (state, key, on_open) = match wander.module.maps[&map_idx].door(wp.x, wp.y, wp.facing) {
+        Some(door) => (door.state, door.key.clone(), door.on_open.clone()),
+        None => (DoorState::Closed, None, None),
+    };
+
+    if state == DoorState::Locked {
+        match key {
+            Some(key) if wander.has_item(&key) => {
+                log.add_line(
+                    &format!(
+                        "You unlock the door with {}.",
+                        wander.module.item_name(&key)
+                    ),
+                    DEFAULT_TEXT_COLOR,
+                );
+            }
+            _ => {
+                log.add_line(LOCKED_MESSAGE, DEFAULT_TEXT_COLOR);
+                return;
+            }
+        }
+    } else if state == DoorState::Open {
+        // Already open, so something else is in the way
+        log.add_line(BUMP_MESSAGE, DEFAULT_TEXT_COLOR);
+        return;
+    }
+
+    log.add_line(OPEN_MESSAGE, DEFAULT_TEXT_COLOR);
+    wander
+        .module
+        .maps
+        .get_mut(&map_idx)
+        .unwrap()
+        .set_door_state(wp.x, wp.y, wp.facing, DoorState::Open);
+    if let Some(on_open) = on_open {
+        triggers.send(TriggerEvent(on_open));
+    }
+}
+
+/// Moves a light or camera transform to the center of the player's tile,
+/// optionally turning it to look the way the player is facing.
+pub fn place_at_player(
+    map: &RegionMap,
+    wp: &WanderingPlayer,
+    trans: &mut Transform,
+    look_forward: bool,
+) {
+    let (x, y) = map.tile_location(wp.x as f32, wp.y as f32);
+    trans.translation.x = (x * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
+    trans.translation.y = (y * GEOMETRY_SIZE) + (GEOMETRY_SIZE / 2.0);
+    if look_forward {
+        let target = wp.facing.camera_look_at(&trans.translation);
+        trans.look_at(target, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
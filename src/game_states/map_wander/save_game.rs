@@ -0,0 +1,203 @@
+use super::{
+    gamelog::{GameLog, DEFAULT_TEXT_COLOR},
+    player_movement::{place_at_player, MoveOccurred},
+    sprites::{RegionSprite, SpriteRequest},
+    WanderCamera, WanderInput, WanderLight, WanderResource, WanderingPlayer,
+};
+use crate::{
+    game_states::CharacterHeader,
+    module::{
+        game_events::{GameVariables, ScriptState},
+        Direction,
+    },
+    region::region_map::{map_editor::EditHistory, ExploredMap, RegionMap},
+};
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_egui::egui::Color32;
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
+
+const SAVE_DIRECTORY: &str = "saves";
+const QUICKSAVE_FILENAME: &str = "quicksave.ron";
+
+/// A snapshot of a MapWander session, stored alongside the module it
+/// was played from.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub map_idx: usize,
+    pub player: (i32, i32, Direction),
+    pub maps: HashMap<usize, RegionMap>,
+    pub script: ScriptState,
+    pub script_input: Option<WanderInput>,
+    #[serde(default)]
+    pub variables: GameVariables,
+    pub sprites: Vec<SavedSprite>,
+    pub log: Vec<(String, (u8, u8, u8))>,
+    #[serde(default)]
+    pub party: Vec<CharacterHeader>,
+    #[serde(default)]
+    pub inventory: Vec<String>,
+    #[serde(default)]
+    pub explored: HashMap<usize, ExploredMap>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedSprite {
+    pub id: String,
+    pub image: String,
+    pub position: (u32, u32),
+}
+
+/// Inserted as a resource to ask MapWander to restore a save game.
+pub struct PendingLoad(pub SaveGame);
+
+impl SaveGame {
+    pub fn quicksave_path(module_path: &Path) -> PathBuf {
+        module_path.join(SAVE_DIRECTORY).join(QUICKSAVE_FILENAME)
+    }
+
+    pub fn save(&self, module_path: &Path) -> Result<()> {
+        create_dir_all(module_path.join(SAVE_DIRECTORY))?;
+        let save_ron = to_string_pretty(self, PrettyConfig::new())?;
+        std::fs::write(SaveGame::quicksave_path(module_path), save_ron)?;
+        Ok(())
+    }
+
+    pub fn load(module_path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(SaveGame::quicksave_path(module_path))?;
+        Ok(ron::from_str(&data)?)
+    }
+
+    pub fn exists(module_path: &Path) -> bool {
+        SaveGame::quicksave_path(module_path).exists()
+    }
+}
+
+/// F5 saves the current session, F9 restores the last save.
+pub fn save_game_hotkeys(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    wander: Res<WanderResource>,
+    script: Res<ScriptState>,
+    mut log: ResMut<GameLog>,
+    player_query: Query<&WanderingPlayer>,
+    sprite_query: Query<&RegionSprite>,
+) {
+    let module_path = Path::new(&wander.module.base_path);
+
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        let player = player_query.iter().next().unwrap();
+        let save = SaveGame {
+            map_idx: wander.map_idx,
+            player: (player.x, player.y, player.facing),
+            maps: wander.module.maps.clone(),
+            script: script.clone(),
+            script_input: wander.script_input.clone(),
+            variables: wander.variables.clone(),
+            sprites: sprite_query
+                .iter()
+                .map(|s| SavedSprite {
+                    id: s.id.clone(),
+                    image: s.image.clone(),
+                    // Sprites that are walking somewhere load already there
+                    position: s.walk.as_ref().map_or(s.position, |w| w.destination),
+                })
+                .collect(),
+            log: log.lines(),
+            party: wander.party.clone(),
+            inventory: wander.inventory.clone(),
+            explored: wander.explored.clone(),
+        };
+        match save.save(module_path) {
+            Ok(_) => log.add_line("Game saved.", DEFAULT_TEXT_COLOR),
+            Err(e) => {
+                println!("Unable to save game: {:?}", e);
+                log.add_line(&format!("Unable to save game: {}", e), Color32::RED);
+            }
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        match SaveGame::load(module_path) {
+            Ok(save) => commands.insert_resource(PendingLoad(save)),
+            Err(e) => {
+                println!("Unable to load game: {:?}", e);
+                log.add_line(&format!("Unable to load game: {}", e), Color32::RED);
+            }
+        }
+    }
+}
+
+/// Restores a save game requested with `PendingLoad`, either from the
+/// main menu or the quickload key.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_pending_load(
+    mut commands: Commands,
+    pending: Option<Res<PendingLoad>>,
+    mut wander: ResMut<WanderResource>,
+    mut script: ResMut<ScriptState>,
+    mut log: ResMut<GameLog>,
+    mut player_query: Query<&mut WanderingPlayer>,
+    mut move_set: QuerySet<(
+        QueryState<(&WanderLight, &mut Transform)>,
+        QueryState<(&WanderCamera, &mut Transform)>,
+    )>,
+    sprite_query: Query<(Entity, &RegionSprite)>,
+    mut sprite_request: EventWriter<SpriteRequest>,
+    mut move_occurred: EventWriter<MoveOccurred>,
+) {
+    if let Some(pending) = pending {
+        let save = &pending.0;
+
+        wander.module.maps = save.maps.clone();
+        // Edits made before loading no longer apply to these maps
+        wander.editor_history = EditHistory::default();
+        wander.map_idx = save.map_idx;
+        wander.script_input = save.script_input.clone();
+        wander.variables = save.variables.clone();
+        wander.party = save.party.clone();
+        wander.inventory = save.inventory.clone();
+        wander.explored = save.explored.clone();
+        wander
+            .module
+            .maps
+            .get_mut(&save.map_idx)
+            .unwrap()
+            .needs_rebuild = true;
+        *script = save.script.clone();
+        log.restore(&save.log);
+
+        let map = &wander.module.maps[&save.map_idx];
+        player_query.iter_mut().for_each(|mut wp| {
+            wp.x = save.player.0;
+            wp.y = save.player.1;
+            wp.facing = save.player.2;
+            move_set.q0().iter_mut().for_each(|(_, mut trans)| {
+                place_at_player(map, &wp, &mut trans, false);
+            });
+            move_set.q1().iter_mut().for_each(|(_, mut trans)| {
+                place_at_player(map, &wp, &mut trans, true);
+            });
+        });
+
+        sprite_query
+            .iter()
+            .for_each(|(e, _)| commands.entity(e).despawn());
+        for sprite in save.sprites.iter() {
+            sprite_request.send(SpriteRequest::Spawn {
+                id: sprite.id.clone(),
+                position: sprite.position,
+                image: sprite.image.clone(),
+            });
+        }
+        move_occurred.send(MoveOccurred {});
+
+        commands.remove_resource::<PendingLoad>();
+    }
+}
@@ -1,9 +1,10 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, time::Duration};
 
 use crate::region::{region_assets::RegionAssets, region_map::geometry::GEOMETRY_SIZE};
 
-use super::{player_movement::MoveOccurred, WanderingPlayer};
+use super::{player_movement::MoveOccurred, WanderResource, WanderingPlayer};
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SpriteRequest {
@@ -19,16 +20,41 @@ pub enum SpriteRequest {
         id: String,
         position: (u32, u32),
     },
+    /// Walks around walls to `destination`, one tile every `step_ms`
+    Walk {
+        id: String,
+        destination: (u32, u32),
+        step_ms: u64,
+    },
 }
 
 #[derive(Component)]
-pub struct RegionSprite(pub String);
+pub struct RegionSprite {
+    pub id: String,
+    pub image: String,
+    pub position: (u32, u32),
+    pub walk: Option<SpriteWalk>,
+}
+
+/// A sprite part way along a path.
+pub struct SpriteWalk {
+    pub destination: (u32, u32),
+    pub step_ms: u64,
+    path: VecDeque<(u32, u32)>,
+    timer: Timer,
+}
+
+fn place_sprite(trans: &mut Transform, position: (u32, u32)) {
+    trans.translation.x = 0.0 - ((position.0 as f32 - 0.5) * GEOMETRY_SIZE);
+    trans.translation.y = (position.1 as f32 + 0.5) * GEOMETRY_SIZE;
+}
 
 pub fn region_sprites(
     mut events: EventReader<SpriteRequest>,
     mut commands: Commands,
     assets: Res<RegionAssets>,
-    mut move_query: Query<(Entity, &RegionSprite, &mut Transform)>,
+    wander: Res<WanderResource>,
+    mut move_query: Query<(Entity, &mut RegionSprite, &mut Transform)>,
 ) {
     for event in events.iter() {
         match event {
@@ -48,19 +74,54 @@ pub fn region_sprites(
                         ),
                         ..Default::default()
                     })
-                    .insert(RegionSprite(id.clone()));
+                    .insert(RegionSprite {
+                        id: id.clone(),
+                        image: image.clone(),
+                        position: *position,
+                        walk: None,
+                    });
             }
             SpriteRequest::Move { id, position } => {
-                move_query.iter_mut().for_each(|(_, tag, mut pos)| {
-                    if tag.0.as_str() == id.as_str() {
-                        pos.translation.x = 0.0 - ((position.0 as f32 - 0.5) * GEOMETRY_SIZE);
-                        pos.translation.y = (position.1 as f32 + 0.5) * GEOMETRY_SIZE;
+                move_query.iter_mut().for_each(|(_, mut tag, mut pos)| {
+                    if tag.id.as_str() == id.as_str() {
+                        tag.position = *position;
+                        tag.walk = None;
+                        place_sprite(&mut pos, *position);
+                    }
+                });
+            }
+            SpriteRequest::Walk {
+                id,
+                destination,
+                step_ms,
+            } => {
+                let map = &wander.module.maps[&wander.map_idx];
+                move_query.iter_mut().for_each(|(_, mut tag, _)| {
+                    if tag.id.as_str() != id.as_str() {
+                        return;
+                    }
+                    let from = (tag.position.0 as i32, tag.position.1 as i32);
+                    let to = (destination.0 as i32, destination.1 as i32);
+                    match map.find_path(from, to) {
+                        Some(path) => {
+                            tag.walk = Some(SpriteWalk {
+                                destination: *destination,
+                                step_ms: *step_ms,
+                                path: path.iter().map(|(x, y)| (*x as u32, *y as u32)).collect(),
+                                // A zero length repeating timer panics when ticked
+                                timer: Timer::new(
+                                    Duration::from_millis(u64::max(1, *step_ms)),
+                                    true,
+                                ),
+                            });
+                        }
+                        None => println!("Sprite {} has no way to walk to {:?}", id, destination),
                     }
                 });
             }
             SpriteRequest::Remove { id } => {
                 move_query.iter().for_each(|(e, tag, _)| {
-                    if tag.0.as_str() == id.as_str() {
+                    if tag.id.as_str() == id.as_str() {
                         commands.entity(e).despawn();
                     }
                 });
@@ -88,3 +149,39 @@ pub fn billboarding(
             });
     }
 }
+
+/// Moves walking sprites one tile along their path each time their timer
+/// runs out.
+pub fn sprite_walking(time: Res<Time>, mut move_query: Query<(&mut RegionSprite, &mut Transform)>) {
+    move_query.iter_mut().for_each(|(mut tag, mut pos)| {
+        let mut position = None;
+        if let Some(walk) = &mut tag.walk {
+            walk.timer.tick(time.delta());
+            if walk.timer.just_finished() {
+                position = walk.path.pop_front();
+            }
+        }
+        if let Some(position) = position {
+            tag.position = position;
+            place_sprite(&mut pos, position);
+        }
+        if tag.walk.as_ref().map_or(false, |w| w.path.is_empty()) {
+            tag.walk = None;
+        }
+    });
+}
+
+/// Hides sprites the party has no line of sight to.
+pub fn sprite_visibility(
+    wander: Res<WanderResource>,
+    player_query: Query<&WanderingPlayer>,
+    mut sprite_query: Query<(&RegionSprite, &mut Visibility)>,
+) {
+    let map = &wander.module.maps[&wander.map_idx];
+    if let Some(player) = player_query.iter().next() {
+        sprite_query.iter_mut().for_each(|(sprite, mut visible)| {
+            let position = (sprite.position.0 as i32, sprite.position.1 as i32);
+            visible.is_visible = map.can_see((player.x, player.y), position);
+        });
+    }
+}
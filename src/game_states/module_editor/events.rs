@@ -1,13 +1,26 @@
-use super::ModuleResource;
-use crate::game_states::player_movement::PlayerMoveRequest;
+use super::{
+    step_editor::{edit_step, StepChoices},
+    ModuleResource,
+};
 use crate::module::game_events::EventPicker;
 use crate::module::game_events::GameEvent;
-use crate::module::game_events::GameEventStep;
+use crate::region::region_map::map_editor::EditHistory;
 use bevy_egui::egui;
 use bevy_egui::EguiContext;
 
+/// Something done to one of an event's steps, applied once the step list
+/// has been drawn.
+enum StepAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Duplicate(usize),
+    InsertBefore(usize),
+    Delete(usize),
+}
+
 pub fn events(egui_context: &EguiContext, module_res: &mut ModuleResource) {
     if module_res.show_events {
+        let mut confirmed_delete = None;
         egui::Window::new("Events")
             .title_bar(true)
             .show(egui_context.ctx(), |ui| {
@@ -17,20 +30,64 @@ pub fn events(egui_context: &EguiContext, module_res: &mut ModuleResource) {
                     let new_event = GameEvent {
                         tag: module_res.new_event_tag.clone(),
                         steps: Vec::new(),
+                        filename: module_res.module.events.filename.clone(),
                     };
                     module_res.module.events.events.push(new_event);
                 }
                 ui.separator();
                 let mut edit_event = None;
-                for e in module_res.module.events.events.iter() {
-                    if ui.button(&e.tag).clicked() {
-                        edit_event = Some(e.tag.clone());
-                    }
+                let mut delete_event = None;
+                let module = &module_res.module;
+                for e in module.events.events.iter() {
+                    let locked = module.is_event_locked(&e.tag);
+                    ui.horizontal(|ui| {
+                        if ui.button(&e.tag).clicked() {
+                            edit_event = Some(e.tag.clone());
+                        }
+                        if ui
+                            .add_enabled(!locked, egui::Button::new("Delete").small())
+                            .clicked()
+                        {
+                            delete_event = Some(e.tag.clone());
+                        }
+                    });
                 }
-                if edit_event.is_some() {
-                    module_res.editing_event = edit_event;
+                if let Some(tag) = edit_event {
+                    module_res.rename_event = tag.clone();
+                    module_res.editing_event = Some(tag);
+                }
+                if delete_event.is_some() {
+                    module_res.deleting_event = delete_event;
+                }
+
+                if let Some(tag) = module_res.deleting_event.clone() {
+                    ui.separator();
+                    let uses = module_res.module.count_event_references(&tag);
+                    ui.label(format!(
+                        "Delete '{}'? It is used in {} places, which will be cleared.",
+                        tag, uses
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Delete").clicked() {
+                            confirmed_delete = Some(tag);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            module_res.deleting_event = None;
+                        }
+                    });
                 }
             });
+
+        if let Some(tag) = confirmed_delete {
+            if module_res.module.delete_event(&tag) {
+                // Map edits may have set triggers that no longer exist
+                module_res.map_history = EditHistory::default();
+                if module_res.editing_event.as_ref() == Some(&tag) {
+                    module_res.editing_event = None;
+                }
+            }
+            module_res.deleting_event = None;
+        }
     }
 }
 
@@ -38,90 +95,133 @@ pub fn event_editor(egui_context: &EguiContext, module_res: &mut ModuleResource)
     if module_res.editing_event.is_some() {
         let tag = module_res.editing_event.clone().unwrap();
         let mut next_step = module_res.new_event_step;
-        if let Some(event) = module_res
-            .module
-            .events
-            .events
-            .iter_mut()
-            .find(|e| e.tag.eq(&tag))
-        {
+        let mut new_name = module_res.rename_event.clone();
+        let mut rename = false;
+        let locked = module_res.module.is_event_locked(&tag);
+        let module = &mut module_res.module;
+        let mut event_tags: Vec<String> =
+            module.events.events.iter().map(|e| e.tag.clone()).collect();
+        event_tags.sort();
+        let can_rename = !locked && !new_name.is_empty() && !event_tags.contains(&new_name);
+        let choices = StepChoices {
+            events: event_tags,
+            maps: &module.maps,
+            ui_images: &module.ui_images,
+            sprites: &module.sprites,
+            encounters: &module.encounters,
+            items: &module.items,
+        };
+        if let Some(event) = module.events.events.iter_mut().find(|e| e.tag.eq(&tag)) {
             egui::Window::new(format!("Event: {}", tag))
                 .title_bar(true)
                 .resizable(true)
                 .show(egui_context.ctx(), |ui| {
-                    ui.text_edit_singleline(&mut event.tag);
-
-                    egui::ComboBox::from_label("New Step").show_ui(ui, |ui| {
-                        ui.selectable_value(&mut next_step, EventPicker::LogText, "Log");
-                        ui.selectable_value(&mut next_step, EventPicker::ClearLog, "Clear Log");
-                        ui.selectable_value(&mut next_step, EventPicker::CallEvent, "Call");
-                        ui.selectable_value(&mut next_step, EventPicker::PauseMs, "Pause Delay MS");
-                        ui.selectable_value(&mut next_step, EventPicker::MovePlayer, "Move Player");
+                    if event.is_read_only() {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            format!(
+                                "Written in {}: edit that file to change it.",
+                                event.filename
+                            ),
+                        );
+                    } else if locked {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Called from a script file, so it can't be renamed.",
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut new_name);
+                        rename = ui
+                            .add_enabled(can_rename, egui::Button::new("Rename"))
+                            .clicked();
                     });
+                    ui.set_enabled(!event.is_read_only());
 
-                    if ui.button("Add Step").clicked() {
-                        match next_step {
-                            EventPicker::LogText => {
-                                event.steps.push(GameEventStep::LogText {
-                                    text: "Hello".to_string(),
-                                    color: None,
-                                });
-                            }
-                            EventPicker::ClearLog => {
-                                event.steps.push(GameEventStep::ClearLog);
-                            }
-                            EventPicker::PauseMs => {
-                                event.steps.push(GameEventStep::PauseMs(33));
-                            }
-                            EventPicker::CallEvent => {
-                                event.steps.push(GameEventStep::CallEvent(String::new()));
-                            }
-                            EventPicker::MovePlayer => {
-                                event.steps.push(GameEventStep::MovePlayer(
-                                    PlayerMoveRequest::Forwards,
-                                    1000,
-                                ));
-                            }
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_label("New Step")
+                            .selected_text(next_step.name())
+                            .show_ui(ui, |ui| {
+                                for picker in EventPicker::ALL {
+                                    ui.selectable_value(&mut next_step, picker, picker.name());
+                                }
+                            });
+                        if ui.button("Add Step").clicked() {
+                            event.steps.push(next_step.new_step());
                         }
-                    }
+                    });
 
                     // List steps
-                    for (line, e) in event.steps.iter_mut().enumerate() {
-                        match e {
-                            GameEventStep::LogText { text, .. } => {
-                                ui.label(format!("{} : Log Text", line));
-                                ui.text_edit_singleline(text);
-                            }
-                            GameEventStep::ClearLog => {
-                                ui.label(format!("{} : Clear Log", line));
-                            }
-                            GameEventStep::PauseMs(ms) => {
-                                ui.label(format!("{} : Pause Ms", line));
-                                ui.add(egui::Slider::new(ms, 1..=10000));
-                            }
-                            GameEventStep::CallEvent(tag) => {
-                                ui.label(format!("{} : Call Event", line));
-                                ui.text_edit_singleline(tag);
-                            }
-                            GameEventStep::MovePlayer(..) => {
-                                ui.label(format!("{} : Move Player", line));
-                                // TODO: Editor support
-                            }
-                            GameEventStep::ChangeMap { .. } => {
-                                ui.label(format!("{} : Change Map", line));
-                                // TODO: Editor support
-                            }
-                            GameEventStep::InputBranch { .. } => {
-                                ui.label("Input branch");
-                            }
-                            GameEventStep::Sprite(..) => {
-                                ui.label("Sprite Action");
+                    let mut action = None;
+                    let count = event.steps.len();
+                    egui::ScrollArea::vertical()
+                        .max_height(600.0)
+                        .show(ui, |ui| {
+                            for (line, step) in event.steps.iter_mut().enumerate() {
+                                ui.push_id(line, |ui| {
+                                    ui.separator();
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!("{} : {}", line, step.picker().name()));
+                                        if let Some(a) = step_buttons(ui, line, count) {
+                                            action = Some(a);
+                                        }
+                                    });
+                                    edit_step(ui, step, &choices);
+                                });
                             }
-                            _ => {}
+                        });
+
+                    match action {
+                        Some(StepAction::MoveUp(i)) => event.steps.swap(i, i - 1),
+                        Some(StepAction::MoveDown(i)) => event.steps.swap(i, i + 1),
+                        Some(StepAction::Duplicate(i)) => {
+                            let step = event.steps[i].clone();
+                            event.steps.insert(i + 1, step);
                         }
+                        Some(StepAction::InsertBefore(i)) => {
+                            event.steps.insert(i, next_step.new_step())
+                        }
+                        Some(StepAction::Delete(i)) => {
+                            event.steps.remove(i);
+                        }
+                        None => {}
                     }
                 });
         }
+
+        if rename && module_res.module.rename_event(&tag, &new_name) {
+            module_res.map_history = EditHistory::default();
+            module_res.editing_event = Some(new_name.clone());
+        }
+        module_res.rename_event = new_name;
         module_res.new_event_step = next_step;
     }
 }
+
+/// Up, down, duplicate, insert (the New Step type) and delete for one
+/// step.
+fn step_buttons(ui: &mut egui::Ui, line: usize, count: usize) -> Option<StepAction> {
+    let mut action = None;
+    if ui
+        .add_enabled(line > 0, egui::Button::new("Up").small())
+        .clicked()
+    {
+        action = Some(StepAction::MoveUp(line));
+    }
+    if ui
+        .add_enabled(line + 1 < count, egui::Button::new("Down").small())
+        .clicked()
+    {
+        action = Some(StepAction::MoveDown(line));
+    }
+    if ui.small_button("Duplicate").clicked() {
+        action = Some(StepAction::Duplicate(line));
+    }
+    if ui.small_button("Insert Above").clicked() {
+        action = Some(StepAction::InsertBefore(line));
+    }
+    if ui.small_button("Delete").clicked() {
+        action = Some(StepAction::Delete(line));
+    }
+    action
+}
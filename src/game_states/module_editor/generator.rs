@@ -0,0 +1,177 @@
+use super::{maps::add_map, ModuleResource};
+use crate::{
+    module::Module,
+    region::region_map::{
+        generator::{generate_map, GeneratorAlgorithm, GeneratorSettings},
+        RegionBoundaryType, RegionMap, RegionTileType,
+    },
+};
+use bevy_egui::egui::{self, Color32, Pos2, Rect, Sense, Stroke, Widget};
+use bracket_random::prelude::RandomNumberGenerator;
+
+const PREVIEW_SIZE: f32 = 256.0;
+
+pub struct MapGenerator {
+    settings: GeneratorSettings,
+    /// The settings the preview was made with, and the map itself
+    preview: Option<(GeneratorSettings, RegionMap)>,
+}
+
+impl MapGenerator {
+    /// Starts out using the module's first two materials for floors and
+    /// walls.
+    pub fn new(module: &Module) -> Self {
+        let mut settings = GeneratorSettings::default();
+        let mut materials: Vec<usize> = module.materials.keys().copied().collect();
+        materials.sort_unstable();
+        if let Some(floor) = materials.first() {
+            settings.floor_material = *floor as u32;
+            settings.wall_material = *materials.get(1).unwrap_or(floor) as u32;
+        }
+        Self {
+            settings,
+            preview: None,
+        }
+    }
+}
+
+pub fn map_generator(ui: &mut egui::Ui, module_res: &mut ModuleResource) {
+    let mats = &module_res.module.materials;
+    let generator = &mut module_res.generator;
+    let settings = &mut generator.settings;
+
+    ui.horizontal(|ui| {
+        for algorithm in GeneratorAlgorithm::ALL {
+            ui.radio_value(&mut settings.algorithm, algorithm, algorithm.name());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Seed");
+        ui.add(egui::DragValue::new(&mut settings.seed));
+        if ui.button("Random").clicked() {
+            settings.seed = RandomNumberGenerator::new().next_u64();
+        }
+    });
+    ui.label("Width");
+    egui::Slider::new(&mut settings.size.0, 2..=64).ui(ui);
+    ui.label("Height");
+    egui::Slider::new(&mut settings.size.1, 2..=64).ui(ui);
+
+    match settings.algorithm {
+        GeneratorAlgorithm::Rooms => {
+            ui.label("Rooms to try");
+            egui::Slider::new(&mut settings.max_rooms, 1..=50).ui(ui);
+            ui.label("Smallest room");
+            egui::Slider::new(&mut settings.room_size.0, 1..=16).ui(ui);
+            ui.label("Largest room");
+            egui::Slider::new(&mut settings.room_size.1, 1..=16).ui(ui);
+            settings.room_size.1 = u32::max(settings.room_size.0, settings.room_size.1);
+        }
+        GeneratorAlgorithm::Caves => {
+            ui.label("Rock %");
+            egui::Slider::new(&mut settings.fill_percent, 0..=100).ui(ui);
+            ui.label("Smoothing passes");
+            egui::Slider::new(&mut settings.iterations, 0..=10).ui(ui);
+        }
+        GeneratorAlgorithm::Maze => {}
+    }
+
+    let material_name = |idx: u32| {
+        mats.get(&(idx as usize))
+            .map_or_else(|| "Missing".to_string(), |m| m.0.clone())
+    };
+    egui::ComboBox::from_label("Floor Material")
+        .selected_text(material_name(settings.floor_material))
+        .show_ui(ui, |ui| {
+            for (i, v) in mats.iter() {
+                ui.selectable_value(&mut settings.floor_material, *i as u32, v.0.clone());
+            }
+        });
+    egui::ComboBox::from_label("Wall Material")
+        .selected_text(material_name(settings.wall_material))
+        .show_ui(ui, |ui| {
+            for (i, v) in mats.iter() {
+                ui.selectable_value(&mut settings.wall_material, *i as u32, v.0.clone());
+            }
+        });
+    let mut has_ceiling = settings.ceiling_material.is_some();
+    ui.checkbox(&mut has_ceiling, "Ceiling");
+    if has_ceiling {
+        let mut ceiling = settings.ceiling_material.unwrap_or(settings.wall_material);
+        egui::ComboBox::from_label("Ceiling Material")
+            .selected_text(material_name(ceiling))
+            .show_ui(ui, |ui| {
+                for (i, v) in mats.iter() {
+                    ui.selectable_value(&mut ceiling, *i as u32, v.0.clone());
+                }
+            });
+        settings.ceiling_material = Some(ceiling);
+    } else {
+        settings.ceiling_material = None;
+    }
+
+    // Regenerate whenever the settings change
+    if generator.preview.as_ref().map(|(s, _)| s) != Some(&generator.settings) {
+        generator.preview = Some((
+            generator.settings.clone(),
+            generate_map(&generator.settings),
+        ));
+    }
+    let mut add = false;
+    if let Some((_, map)) = &generator.preview {
+        draw_preview(ui, map);
+        add = ui.button("Add to Module").clicked();
+    }
+
+    if add {
+        if let Some((_, map)) = module_res.generator.preview.clone() {
+            add_map(&mut module_res.module, map);
+        }
+    }
+}
+
+/// A small top-down view of a generated map.
+fn draw_preview(ui: &mut egui::Ui, map: &RegionMap) {
+    let (response, painter) = ui.allocate_painter(egui::Vec2::splat(PREVIEW_SIZE), Sense::hover());
+    let cell = PREVIEW_SIZE / u32::max(map.size.0, map.size.1) as f32;
+    let origin = response.rect.min;
+    let corner = |x: i32, y: i32| Pos2::new(origin.x + x as f32 * cell, origin.y + y as f32 * cell);
+
+    painter.rect_filled(response.rect, 0.0, Color32::BLACK);
+    for y in 0..map.size.1 as i32 {
+        for x in 0..map.size.0 as i32 {
+            let tile = &map.tiles[map.tile_idx(x, y)];
+            if tile.tile_type == RegionTileType::Floor {
+                painter.rect_filled(
+                    Rect::from_two_pos(corner(x, y), corner(x + 1, y + 1)),
+                    0.0,
+                    Color32::from_gray(80),
+                );
+            }
+            let edges = [
+                (corner(x, y), corner(x + 1, y)),
+                (corner(x, y + 1), corner(x + 1, y + 1)),
+                (corner(x + 1, y), corner(x + 1, y + 1)),
+                (corner(x, y), corner(x, y + 1)),
+            ];
+            for (boundary, (a, b)) in tile.boundaries.iter().zip(edges) {
+                let color = match boundary.0 {
+                    RegionBoundaryType::Wall => Color32::WHITE,
+                    RegionBoundaryType::Opening | RegionBoundaryType::Door => Color32::YELLOW,
+                    RegionBoundaryType::None => continue,
+                };
+                painter.line_segment([a, b], Stroke::new(1.0, color));
+            }
+        }
+    }
+
+    let (sx, sy, _) = map.starting_location;
+    painter.circle_filled(
+        Pos2::new(
+            origin.x + (sx as f32 + 0.5) * cell,
+            origin.y + (sy as f32 + 0.5) * cell,
+        ),
+        cell / 3.0,
+        Color32::RED,
+    );
+}
@@ -1,10 +1,32 @@
-use super::ModuleResource;
+use super::{generator, ModuleResource};
+use crate::{
+    module::Module,
+    region::region_map::{map_editor::EditHistory, Anchor, RegionMap},
+};
 use bevy_egui::egui;
 use bevy_egui::egui::Widget;
 use bevy_egui::EguiContext;
+use std::collections::HashMap;
+
+/// Adds a map to the module under the next free index.
+pub fn add_map(module: &mut Module, mut map: RegionMap) {
+    let id = module.next_map_index;
+    module.next_map_index += 1;
+    map.index = id;
+    map.filename = format!("{}/maps/map{}.ron", module.base_path, id);
+    module.maps.insert(id, map);
+}
+
+/// Settings for a map that is being resized.
+pub struct MapResize {
+    map: usize,
+    size: (u32, u32),
+    anchor: (Anchor, Anchor),
+}
 
 pub fn maps(egui_context: &EguiContext, module_res: &mut ModuleResource) {
     if module_res.show_maps {
+        let mut resize_action = None;
         egui::Window::new("Maps in Module")
             .title_bar(true)
             .show(egui_context.ctx(), |ui| {
@@ -15,26 +37,112 @@ pub fn maps(egui_context: &EguiContext, module_res: &mut ModuleResource) {
                 ui.label("Height");
                 egui::Slider::new(&mut module_res.new_map.size.1, 1..=64).ui(ui);
                 if ui.button("Create Map").clicked() {
-                    let id = module_res.module.next_map_index;
-                    module_res.module.next_map_index += 1;
-                    let m = module_res.new_map.clone();
-                    module_res.module.maps.insert(id, m);
+                    let (width, height) = module_res.new_map.size;
+                    let mut m = RegionMap::new(width, height);
+                    m.name = module_res.new_map.name.clone();
+                    add_map(&mut module_res.module, m);
                 }
+                egui::CollapsingHeader::new("Generate Map")
+                    .show(ui, |ui| generator::map_generator(ui, module_res));
 
                 ui.separator();
                 if module_res.module.maps.is_empty() {
                     ui.label("There are no maps");
                 } else {
                     let mut new_map: Option<usize> = None;
-                    for (k, v) in module_res.module.maps.iter() {
-                        if ui.button(&v.name).clicked() {
-                            new_map = Some(*k);
+                    let mut keys: Vec<usize> = module_res.module.maps.keys().copied().collect();
+                    keys.sort_unstable();
+                    for k in keys {
+                        let v = &module_res.module.maps[&k];
+                        let mut resize = false;
+                        ui.horizontal(|ui| {
+                            if ui.button(&v.name).clicked() {
+                                new_map = Some(k);
+                            }
+                            ui.label(format!("{}x{}", v.size.0, v.size.1));
+                            resize = ui.button("Resize").clicked();
+                        });
+                        if resize {
+                            module_res.resizing = Some(MapResize {
+                                map: k,
+                                size: v.size,
+                                anchor: (Anchor::Middle, Anchor::Middle),
+                            });
                         }
                     }
                     if new_map.is_some() {
                         module_res.editing_map = new_map;
                     }
                 }
+
+                if let Some(resize) = &mut module_res.resizing {
+                    ui.separator();
+                    resize_action = resize_ui(ui, resize, &module_res.module.maps);
+                }
             });
+
+        match resize_action {
+            Some(ResizeAction::Apply) => {
+                if let Some(resize) = module_res.resizing.take() {
+                    module_res
+                        .module
+                        .resize_map(resize.map, resize.size, resize.anchor);
+                    // Recorded edits refer to tile positions before the resize
+                    module_res.map_history = EditHistory::default();
+                }
+            }
+            Some(ResizeAction::Cancel) => module_res.resizing = None,
+            None => {}
+        }
     }
 }
+
+enum ResizeAction {
+    Apply,
+    Cancel,
+}
+
+fn resize_ui(
+    ui: &mut egui::Ui,
+    resize: &mut MapResize,
+    maps: &HashMap<usize, RegionMap>,
+) -> Option<ResizeAction> {
+    let map = match maps.get(&resize.map) {
+        Some(map) => map,
+        None => return Some(ResizeAction::Cancel),
+    };
+    ui.label(format!(
+        "Resize {} from {}x{}",
+        map.name, map.size.0, map.size.1
+    ));
+    ui.label("Width");
+    egui::Slider::new(&mut resize.size.0, 1..=64).ui(ui);
+    ui.label("Height");
+    egui::Slider::new(&mut resize.size.1, 1..=64).ui(ui);
+
+    // Pick the edge or corner that stays put
+    ui.label("Anchor");
+    egui::Grid::new("resize_anchor").show(ui, |ui| {
+        for vertical in Anchor::ALL {
+            for horizontal in Anchor::ALL {
+                let anchor = (horizontal, vertical);
+                let label = if resize.anchor == anchor { "X" } else { " " };
+                if ui.button(label).clicked() {
+                    resize.anchor = anchor;
+                }
+            }
+            ui.end_row();
+        }
+    });
+
+    let mut action = None;
+    ui.horizontal(|ui| {
+        if ui.button("Apply").clicked() {
+            action = Some(ResizeAction::Apply);
+        }
+        if ui.button("Cancel").clicked() {
+            action = Some(ResizeAction::Cancel);
+        }
+    });
+    action
+}
@@ -1,4 +1,5 @@
 use super::ModuleResource;
+use crate::modules::validate_module;
 use bevy_egui::egui;
 use bevy_egui::EguiContext;
 
@@ -18,6 +19,9 @@ pub fn editor_menu(egui_context: &EguiContext, module_res: &mut ModuleResource)
                 if ui.button("Event Scripting").clicked() {
                     module_res.show_events = !module_res.show_events;
                 }
+                if ui.button("Validate").clicked() {
+                    module_res.validation = Some(validate_module(&module_res.module));
+                }
                 if ui.button("Save").clicked() {
                     module_res.module.save();
                 }
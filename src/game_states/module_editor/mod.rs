@@ -1,18 +1,22 @@
 use super::ModuleSelector;
 use crate::{
     module::{game_events::EventPicker, Module},
+    modules::ValidationIssue,
     region::region_map::{
-        map_editor::{MapEditor, MapEditorSettings},
+        map_editor::{EditHistory, MapEditor, MapEditorSettings},
         RegionMap,
     },
 };
 use bevy::prelude::*;
 use bevy_egui::EguiContext;
 mod events;
+mod generator;
 mod maps;
 mod materials;
 mod menu;
 mod module_info;
+mod step_editor;
+mod validation;
 
 pub struct ModuleResource {
     pub module: Module,
@@ -22,12 +26,20 @@ pub struct ModuleResource {
     new_material_name: String,
     show_maps: bool,
     new_map: RegionMap,
+    resizing: Option<maps::MapResize>,
+    generator: generator::MapGenerator,
     editing_map: Option<usize>,
     editor_settings: MapEditorSettings,
+    map_history: EditHistory,
     show_events: bool,
     new_event_tag: String,
     editing_event: Option<String>,
+    /// Text of the rename box in the event editor
+    rename_event: String,
+    /// Event waiting for the user to confirm its deletion
+    deleting_event: Option<String>,
     new_event_step: EventPicker,
+    validation: Option<Vec<ValidationIssue>>,
 }
 
 pub fn module_editor(egui_context: ResMut<EguiContext>, mut module_res: ResMut<ModuleResource>) {
@@ -38,12 +50,21 @@ pub fn module_editor(egui_context: ResMut<EguiContext>, mut module_res: ResMut<M
 
     if let Some(map_id) = module_res.editing_map {
         let mut es = module_res.editor_settings.clone();
-        MapEditor::render_in_module(egui_context.ctx(), &mut es, &mut module_res.module, map_id);
+        let mut history = std::mem::take(&mut module_res.map_history);
+        MapEditor::render_in_module(
+            egui_context.ctx(),
+            &mut es,
+            &mut history,
+            &mut module_res.module,
+            map_id,
+        );
         module_res.editor_settings = es;
+        module_res.map_history = history;
     }
 
     events::events(&egui_context, &mut module_res);
     events::event_editor(&egui_context, &mut module_res);
+    validation::validation_results(&egui_context, &mut module_res);
 }
 
 pub fn resume_module_editor(mut commands: Commands, startup: Res<ModuleSelector>) {
@@ -57,28 +78,41 @@ pub fn resume_module_editor(mut commands: Commands, startup: Res<ModuleSelector>
             new_material_name: "New Material".to_string(),
             show_maps: false,
             new_map: RegionMap::default(),
+            resizing: None,
+            generator: generator::MapGenerator::new(module),
             editing_map: None,
             editor_settings: MapEditorSettings::default(),
+            map_history: EditHistory::default(),
             show_events: false,
             new_event_tag: String::new(),
             editing_event: None,
+            rename_event: String::new(),
+            deleting_event: None,
             new_event_step: EventPicker::LogText,
+            validation: None,
         });
     } else {
+        let module = Module::default();
         commands.insert_resource(ModuleResource {
-            module: Module::default(),
+            generator: generator::MapGenerator::new(&module),
+            module,
             show_info: false,
             show_materials: false,
             current_material: 0,
             new_material_name: "New Material".to_string(),
             show_maps: false,
             new_map: RegionMap::default(),
+            resizing: None,
             editing_map: None,
             editor_settings: MapEditorSettings::default(),
+            map_history: EditHistory::default(),
             show_events: false,
             new_event_tag: String::new(),
             editing_event: None,
+            rename_event: String::new(),
+            deleting_event: None,
             new_event_step: EventPicker::LogText,
+            validation: None,
         });
     }
 }
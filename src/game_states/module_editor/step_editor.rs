@@ -0,0 +1,432 @@
+use crate::{
+    game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest},
+    module::{
+        game_events::{Comparison, GameEventStep, InputChoice},
+        Direction, Encounter, ItemTemplate,
+    },
+    region::region_map::{DoorState, RegionMap},
+};
+use bevy_egui::egui::{self, Ui};
+use std::collections::HashMap;
+
+const NONE_LABEL: &str = "(none)";
+
+/// The parts of the module a step can refer to, offered by the pickers.
+pub struct StepChoices<'a> {
+    pub events: Vec<String>,
+    pub maps: &'a HashMap<usize, RegionMap>,
+    pub ui_images: &'a [(String, String)],
+    pub sprites: &'a [(String, String)],
+    pub encounters: &'a HashMap<String, Encounter>,
+    pub items: &'a HashMap<String, ItemTemplate>,
+}
+
+/// Widgets for every field of a step.
+pub fn edit_step(ui: &mut Ui, step: &mut GameEventStep, choices: &StepChoices) {
+    match step {
+        GameEventStep::LogText { text, color } => {
+            ui.text_edit_multiline(text);
+            ui.horizontal(|ui| {
+                let mut custom = color.is_some();
+                ui.checkbox(&mut custom, "Color");
+                if custom {
+                    let (r, g, b) = color.unwrap_or((255, 255, 255));
+                    let mut rgb = [r, g, b];
+                    ui.color_edit_button_srgb(&mut rgb);
+                    *color = Some((rgb[0], rgb[1], rgb[2]));
+                } else {
+                    *color = None;
+                }
+            });
+        }
+        GameEventStep::ClearLog => {}
+        GameEventStep::PauseMs(ms) => {
+            ui.add(egui::Slider::new(ms, 1..=10000).text("ms"));
+        }
+        GameEventStep::CallEvent(tag) => event_picker(ui, "Event", tag, choices),
+        GameEventStep::MovePlayer(request, delay) => {
+            move_request(ui, request, choices);
+            ui.add(egui::Slider::new(delay, 0..=10000).text("ms"));
+        }
+        GameEventStep::InputBranch {
+            title,
+            message,
+            portrait,
+            options,
+        } => {
+            labelled_text(ui, "Title", title);
+            ui.label("Message");
+            ui.text_edit_multiline(message);
+            let images: Vec<String> = choices.ui_images.iter().map(|(k, _)| k.clone()).collect();
+            optional_picker(ui, "Portrait", portrait, &images);
+
+            let mut remove = None;
+            for (i, option) in options.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut option.message);
+                        tag_picker(ui, "Goes to", &mut option.branch, &choices.events);
+                        if ui.button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                });
+            }
+            if let Some(i) = remove {
+                options.remove(i);
+            }
+            if ui.button("Add Option").clicked() {
+                options.push(InputChoice {
+                    branch: String::new(),
+                    message: "OK".to_string(),
+                });
+            }
+        }
+        GameEventStep::ChangeMap { index, x, y } => map_position(ui, index, x, y, choices),
+        GameEventStep::Sprite(request) => sprite_request(ui, request, choices),
+        GameEventStep::Battle(encounter) => tag_picker(
+            ui,
+            "Encounter",
+            encounter,
+            &sorted(choices.encounters.keys()),
+        ),
+        GameEventStep::SetFlag { flag, value } => {
+            ui.horizontal(|ui| {
+                labelled_text(ui, "Flag", flag);
+                ui.checkbox(value, "Set");
+            });
+        }
+        GameEventStep::SetNumber { name, value } => {
+            ui.horizontal(|ui| {
+                labelled_text(ui, "Variable", name);
+                ui.add(egui::DragValue::new(value));
+            });
+        }
+        GameEventStep::SetText { name, value } => {
+            labelled_text(ui, "Variable", name);
+            labelled_text(ui, "Text", value);
+        }
+        GameEventStep::AddToVar { name, amount } => {
+            ui.horizontal(|ui| {
+                labelled_text(ui, "Variable", name);
+                ui.label("Add");
+                ui.add(egui::DragValue::new(amount));
+            });
+        }
+        GameEventStep::IfFlag {
+            flag,
+            then_branch,
+            else_branch,
+        } => {
+            labelled_text(ui, "Flag", flag);
+            branches(ui, then_branch, else_branch, choices);
+        }
+        GameEventStep::IfNumber {
+            name,
+            comparison,
+            value,
+            then_branch,
+            else_branch,
+        } => {
+            ui.horizontal(|ui| {
+                labelled_text(ui, "Variable", name);
+                egui::ComboBox::from_id_source("Comparison")
+                    .width(50.0)
+                    .selected_text(comparison.symbol())
+                    .show_ui(ui, |ui| {
+                        for c in Comparison::ALL {
+                            ui.selectable_value(comparison, c, c.symbol());
+                        }
+                    });
+                ui.add(egui::DragValue::new(value));
+            });
+            branches(ui, then_branch, else_branch, choices);
+        }
+        GameEventStep::IfText {
+            name,
+            value,
+            then_branch,
+            else_branch,
+        } => {
+            labelled_text(ui, "Variable", name);
+            labelled_text(ui, "Equals", value);
+            branches(ui, then_branch, else_branch, choices);
+        }
+        GameEventStep::GiveItem(item) | GameEventStep::TakeItem(item) => {
+            item_picker(ui, item, choices)
+        }
+        GameEventStep::IfHasItem {
+            item,
+            then_branch,
+            else_branch,
+        } => {
+            item_picker(ui, item, choices);
+            branches(ui, then_branch, else_branch, choices);
+        }
+        GameEventStep::SetDoor {
+            x,
+            y,
+            direction,
+            state,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("X");
+                ui.add(egui::DragValue::new(x));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(y));
+                egui::ComboBox::from_label("Side")
+                    .selected_text(format!("{:?}", direction))
+                    .show_ui(ui, |ui| {
+                        for d in Direction::ALL {
+                            ui.selectable_value(direction, d, format!("{:?}", d));
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                for s in [DoorState::Open, DoorState::Closed, DoorState::Locked] {
+                    ui.radio_value(state, s, s.name());
+                }
+            });
+        }
+    }
+}
+
+fn labelled_text(ui: &mut Ui, label: &str, text: &mut String) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.text_edit_singleline(text);
+    });
+}
+
+fn sorted<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut keys: Vec<String> = keys.cloned().collect();
+    keys.sort();
+    keys
+}
+
+/// Picks one of `tags`. Whatever is already set stays selected, even if
+/// it isn't in the list.
+fn tag_picker(ui: &mut Ui, label: &str, value: &mut String, tags: &[String]) {
+    let selected = if value.is_empty() {
+        NONE_LABEL.to_string()
+    } else {
+        value.clone()
+    };
+    egui::ComboBox::from_label(label)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for tag in tags.iter() {
+                ui.selectable_value(value, tag.clone(), tag.as_str());
+            }
+        });
+}
+
+fn optional_picker(ui: &mut Ui, label: &str, value: &mut Option<String>, tags: &[String]) {
+    egui::ComboBox::from_label(label)
+        .selected_text(value.clone().unwrap_or_else(|| NONE_LABEL.to_string()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, NONE_LABEL);
+            for tag in tags.iter() {
+                ui.selectable_value(value, Some(tag.clone()), tag.as_str());
+            }
+        });
+}
+
+fn event_picker(ui: &mut Ui, label: &str, tag: &mut String, choices: &StepChoices) {
+    tag_picker(ui, label, tag, &choices.events);
+}
+
+fn branches(
+    ui: &mut Ui,
+    then_branch: &mut String,
+    else_branch: &mut Option<String>,
+    choices: &StepChoices,
+) {
+    event_picker(ui, "Then", then_branch, choices);
+    optional_picker(ui, "Else", else_branch, &choices.events);
+}
+
+fn item_picker(ui: &mut Ui, item: &mut String, choices: &StepChoices) {
+    let selected = match choices.items.get(item.as_str()) {
+        Some(template) => template.name.clone(),
+        None if item.is_empty() => NONE_LABEL.to_string(),
+        None => item.clone(),
+    };
+    egui::ComboBox::from_label("Item")
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for tag in sorted(choices.items.keys()) {
+                let name = choices.items[&tag].name.clone();
+                ui.selectable_value(item, tag, name);
+            }
+        });
+}
+
+/// A map from the module and a tile on it.
+fn map_position(ui: &mut Ui, index: &mut usize, x: &mut u32, y: &mut u32, choices: &StepChoices) {
+    let mut keys: Vec<usize> = choices.maps.keys().copied().collect();
+    keys.sort_unstable();
+    let name = |idx: usize| {
+        choices.maps.get(&idx).map_or_else(
+            || format!("{}: Missing", idx),
+            |m| format!("{}: {}", idx, m.name),
+        )
+    };
+    egui::ComboBox::from_label("Map")
+        .selected_text(name(*index))
+        .show_ui(ui, |ui| {
+            for k in keys {
+                ui.selectable_value(index, k, name(k));
+            }
+        });
+
+    let (width, height) = choices.maps.get(&*index).map_or((1, 1), |m| m.size);
+    ui.horizontal(|ui| {
+        ui.label("X");
+        ui.add(egui::DragValue::new(x).clamp_range(0..=width - 1));
+        ui.label("Y");
+        ui.add(egui::DragValue::new(y).clamp_range(0..=height - 1));
+    });
+}
+
+fn tile_position(ui: &mut Ui, label: &str, position: &mut (u32, u32)) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.label("X");
+        ui.add(egui::DragValue::new(&mut position.0));
+        ui.label("Y");
+        ui.add(egui::DragValue::new(&mut position.1));
+    });
+}
+
+fn move_request(ui: &mut Ui, request: &mut PlayerMoveRequest, choices: &StepChoices) {
+    let kinds = [
+        (PlayerMoveRequest::Forwards, "Forwards"),
+        (PlayerMoveRequest::Backwards, "Backwards"),
+        (PlayerMoveRequest::TurnLeft, "Turn Left"),
+        (PlayerMoveRequest::TurnRight, "Turn Right"),
+        (
+            PlayerMoveRequest::ChangeMap {
+                index: 0,
+                x: 0,
+                y: 0,
+            },
+            "Change Map",
+        ),
+    ];
+    let same_kind = |a: &PlayerMoveRequest, b: &PlayerMoveRequest| {
+        std::mem::discriminant(a) == std::mem::discriminant(b)
+    };
+    let current = kinds
+        .iter()
+        .find(|(k, _)| same_kind(k, request))
+        .map_or("", |(_, name)| *name);
+
+    egui::ComboBox::from_label("Movement")
+        .selected_text(current)
+        .show_ui(ui, |ui| {
+            for (kind, name) in kinds.iter() {
+                if ui
+                    .selectable_label(same_kind(kind, request), *name)
+                    .clicked()
+                    && !same_kind(kind, request)
+                {
+                    *request = *kind;
+                }
+            }
+        });
+
+    if let PlayerMoveRequest::ChangeMap { index, x, y } = request {
+        map_position(ui, index, x, y, choices);
+    }
+}
+
+fn sprite_request(ui: &mut Ui, request: &mut SpriteRequest, choices: &StepChoices) {
+    let (id, position) = match request {
+        SpriteRequest::Spawn { id, position, .. } | SpriteRequest::Move { id, position } => {
+            (id.clone(), *position)
+        }
+        SpriteRequest::Walk {
+            id, destination, ..
+        } => (id.clone(), *destination),
+        SpriteRequest::Remove { id } => (id.clone(), (0, 0)),
+    };
+    let kinds = [
+        (
+            SpriteRequest::Spawn {
+                id: id.clone(),
+                position,
+                image: String::new(),
+            },
+            "Spawn",
+        ),
+        (
+            SpriteRequest::Move {
+                id: id.clone(),
+                position,
+            },
+            "Move",
+        ),
+        (
+            SpriteRequest::Walk {
+                id: id.clone(),
+                destination: position,
+                step_ms: 500,
+            },
+            "Walk",
+        ),
+        (SpriteRequest::Remove { id }, "Remove"),
+    ];
+    let same_kind = |a: &SpriteRequest, b: &SpriteRequest| {
+        std::mem::discriminant(a) == std::mem::discriminant(b)
+    };
+    let current = kinds
+        .iter()
+        .find(|(k, _)| same_kind(k, request))
+        .map_or("", |(_, name)| *name);
+
+    let mut new_kind = None;
+    egui::ComboBox::from_label("Action")
+        .selected_text(current)
+        .show_ui(ui, |ui| {
+            for (kind, name) in kinds.iter() {
+                if ui
+                    .selectable_label(same_kind(kind, request), *name)
+                    .clicked()
+                    && !same_kind(kind, request)
+                {
+                    new_kind = Some(kind.clone());
+                }
+            }
+        });
+    if let Some(kind) = new_kind {
+        *request = kind;
+    }
+
+    match request {
+        SpriteRequest::Spawn {
+            id,
+            position,
+            image,
+        } => {
+            labelled_text(ui, "Sprite ID", id);
+            let images: Vec<String> = choices.sprites.iter().map(|(k, _)| k.clone()).collect();
+            tag_picker(ui, "Image", image, &images);
+            tile_position(ui, "Position", position);
+        }
+        SpriteRequest::Move { id, position } => {
+            labelled_text(ui, "Sprite ID", id);
+            tile_position(ui, "Position", position);
+        }
+        SpriteRequest::Walk {
+            id,
+            destination,
+            step_ms,
+        } => {
+            labelled_text(ui, "Sprite ID", id);
+            tile_position(ui, "Destination", destination);
+            ui.add(egui::Slider::new(step_ms, 50..=5000).text("ms per step"));
+        }
+        SpriteRequest::Remove { id } => labelled_text(ui, "Sprite ID", id),
+    }
+}
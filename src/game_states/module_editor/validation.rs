@@ -0,0 +1,57 @@
+use super::ModuleResource;
+use crate::modules::{validate_module, Severity};
+use bevy_egui::egui::{self, Color32};
+use bevy_egui::EguiContext;
+
+pub fn validation_results(egui_context: &EguiContext, module_res: &mut ModuleResource) {
+    let mut close = false;
+    let mut revalidate = false;
+    if let Some(issues) = &module_res.validation {
+        egui::Window::new("Validation")
+            .default_size(egui::Vec2::new(600.0, 300.0))
+            .title_bar(true)
+            .show(egui_context.ctx(), |ui| {
+                let errors = issues
+                    .iter()
+                    .filter(|i| i.severity == Severity::Error)
+                    .count();
+                ui.label(format!(
+                    "{} errors, {} warnings",
+                    errors,
+                    issues.len() - errors
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Check Again").clicked() {
+                        revalidate = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+                ui.separator();
+
+                if issues.is_empty() {
+                    ui.colored_label(Color32::GREEN, "No problems found.");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for issue in issues.iter() {
+                        let color = match issue.severity {
+                            Severity::Error => Color32::RED,
+                            Severity::Warning => Color32::YELLOW,
+                        };
+                        ui.horizontal(|ui| {
+                            ui.colored_label(color, format!("{:?}", issue.severity));
+                            ui.colored_label(Color32::LIGHT_GRAY, &issue.location);
+                            ui.label(&issue.message);
+                        });
+                    }
+                });
+            });
+    }
+
+    if close {
+        module_res.validation = None;
+    } else if revalidate {
+        module_res.validation = Some(validate_module(&module_res.module));
+    }
+}
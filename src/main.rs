@@ -1,28 +1,20 @@
 use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
 use bevy_egui::EguiPlugin;
-mod game_states;
-mod region;
-use game_states::{
-    asset_loader::*,
-    gamelog::display_game_log,
-    player_movement::{player_move, MoveOccurred, PlayerMoveRequest},
-    sprites::{billboarding, region_sprites, SpriteRequest},
-    *,
+use pyrite_box::{
+    game_states::{
+        asset_loader::*,
+        automap::{display_automap, update_automap},
+        gamelog::display_game_log,
+        inventory::display_inventory,
+        party_roster::display_party,
+        player_movement::{player_move, MoveOccurred, PlayerMoveRequest},
+        save_game::{apply_pending_load, save_game_hotkeys},
+        sprites::{billboarding, region_sprites, sprite_visibility, sprite_walking, SpriteRequest},
+        *,
+    },
+    module::game_events::{event_runner, event_triggers, TriggerEvent},
+    AppState,
 };
-use module::game_events::{event_runner, event_triggers, TriggerEvent};
-mod module;
-mod modules;
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
-pub enum AppState {
-    Loading,
-    MainMenu,
-    ModuleEditor,
-    MapWanderLoader, // Loading screen for the map module
-    MapWander,       // Test mode for the map
-    Battle,
-    CharacterGeneration,
-}
 
 fn main() {
     App::new()
@@ -74,13 +66,32 @@ fn main() {
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(map_wander))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(map_wander_rebuild))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(display_game_log))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(display_party))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(display_inventory))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(update_automap))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(display_automap))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(event_triggers))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(event_runner))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(player_move))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(region_sprites))
         .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(billboarding))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(sprite_walking))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(sprite_visibility))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(save_game_hotkeys))
+        .add_system_set(SystemSet::on_update(AppState::MapWander).with_system(apply_pending_load))
         .add_system_set(SystemSet::on_exit(AppState::MapWander).with_system(exit_map_wander))
         // Battle Mode
         .add_system_set(SystemSet::on_enter(AppState::Battle).with_system(start_battle))
+        .add_system_set(SystemSet::on_update(AppState::Battle).with_system(battle_input))
+        .add_system_set(SystemSet::on_update(AppState::Battle).with_system(battle_ai))
+        .add_system_set(SystemSet::on_update(AppState::Battle).with_system(battle_render))
+        .add_system_set(SystemSet::on_update(AppState::Battle).with_system(battle_ui))
+        .add_system_set(SystemSet::on_exit(AppState::Battle).with_system(exit_battle))
+        // Character Generation
+        .add_system_set(
+            SystemSet::on_enter(AppState::CharacterGeneration).with_system(resume_chargen),
+        )
+        .add_system_set(SystemSet::on_update(AppState::CharacterGeneration).with_system(chargen))
+        .add_system_set(SystemSet::on_exit(AppState::CharacterGeneration).with_system(exit_chargen))
         .run();
 }
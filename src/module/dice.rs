@@ -0,0 +1,59 @@
+use bracket_random::prelude::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+
+/// A dice roll, e.g. 1d8+1
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Dice {
+    pub count: i32,
+    pub sides: i32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn new(count: i32, sides: i32, bonus: i32) -> Self {
+        Self {
+            count,
+            sides,
+            bonus,
+        }
+    }
+
+    /// Dice with no sides can't be rolled; the validator reports them and
+    /// rolling them just gives the bonus.
+    pub fn is_valid(&self) -> bool {
+        self.count >= 0 && self.sides >= 1
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> i32 {
+        if self.count < 1 || self.sides < 1 {
+            return self.bonus;
+        }
+        rng.roll_dice(self.count, self.sides) + self.bonus
+    }
+}
+
+impl std::fmt::Display for Dice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.bonus > 0 {
+            write!(f, "{}d{}+{}", self.count, self.sides, self.bonus)
+        } else if self.bonus < 0 {
+            write!(f, "{}d{}{}", self.count, self.sides, self.bonus)
+        } else {
+            write!(f, "{}d{}", self.count, self.sides)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_dice_roll_the_bonus() {
+        let mut rng = RandomNumberGenerator::seeded(1);
+        assert_eq!(Dice::new(2, 0, 3).roll(&mut rng), 3);
+        assert_eq!(Dice::new(-1, 6, 0).roll(&mut rng), 0);
+        assert!(!Dice::new(1, 0, 0).is_valid());
+        assert!(Dice::new(0, 6, 1).is_valid());
+    }
+}
@@ -11,6 +11,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+    ];
+
     pub fn turn_right(self) -> Direction {
         match self {
             Direction::North => Direction::East,
@@ -29,6 +36,15 @@ impl Direction {
         }
     }
 
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+
     pub fn delta_forward(self) -> (i32, i32) {
         match self {
             Direction::North => (0, -1),
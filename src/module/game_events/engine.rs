@@ -0,0 +1,592 @@
+use super::{EventList, GameEventStep, GameValue, GameVariables, InputChoice, TriggerEvent};
+use crate::{
+    game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest},
+    module::Direction,
+    region::region_map::DoorState,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, time::Duration};
+
+/// Game state that scripts can read and change directly. Everything else
+/// is requested through a `ScriptEffect`.
+pub trait ScriptWorld {
+    fn variables(&mut self) -> &mut GameVariables;
+    fn has_item(&self, tag: &str) -> bool;
+}
+
+/// Something a script step asks the game to do.
+#[derive(Clone)]
+pub enum ScriptEffect {
+    Log {
+        text: String,
+        color: Option<(u8, u8, u8)>,
+    },
+    ClearLog,
+    MovePlayer(PlayerMoveRequest),
+    AskInput {
+        title: String,
+        message: String,
+        portrait: Option<String>,
+        options: Vec<InputChoice>,
+    },
+    Sprite(SpriteRequest),
+    Battle(String),
+    GiveItem(String),
+    TakeItem(String),
+    SetDoor {
+        x: u32,
+        y: u32,
+        direction: Direction,
+        state: DoorState,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    /// A script tried to run an event that doesn't exist
+    MissingEvent(String),
+}
+
+pub enum StepOutcome {
+    /// No script is running or queued
+    Idle,
+    /// Waiting for a pause to finish or for the player to pick an option
+    Blocked,
+    /// A step ran (possibly doing nothing, e.g. reaching the end of an event)
+    Ran(Vec<ScriptEffect>),
+}
+
+/// Represents the execution stack of scripts that run one step per
+/// tick.
+#[derive(Clone, Serialize, Deserialize)]
+struct ScriptStack {
+    stack: Vec<ScriptPoint>,
+}
+
+impl ScriptStack {
+    fn new() -> Self {
+        Self { stack: Vec::new() }
+    }
+
+    fn pop(&mut self) -> Option<ScriptPoint> {
+        self.stack.pop()
+    }
+
+    fn push(&mut self, event: ScriptPoint) {
+        self.stack.push(event);
+    }
+}
+
+/// Represents an execution point within a script
+#[derive(Clone, Serialize, Deserialize)]
+struct ScriptPoint {
+    /// The event tag at which the script points
+    tag: String,
+
+    /// The current execution index
+    line: usize,
+}
+
+/// Current scripting state
+/// Intended to be a resource
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScriptState {
+    event_queue: VecDeque<TriggerEvent>,
+    stack: ScriptStack,
+    #[serde(default)]
+    delay: Option<Duration>,
+    #[serde(default)]
+    awaiting_input: bool,
+}
+
+impl ScriptState {
+    pub fn new() -> Self {
+        Self {
+            event_queue: VecDeque::new(),
+            stack: ScriptStack::new(),
+            delay: None,
+            awaiting_input: false,
+        }
+    }
+
+    /// Queue an event to run once the current script has finished.
+    pub fn trigger(&mut self, tag: &str) {
+        self.event_queue.push_front(TriggerEvent(tag.to_string()));
+    }
+
+    /// The player picked an option from an `InputBranch`. The branch runs
+    /// next, then the script carries on after the `InputBranch`.
+    pub fn choose_input(&mut self, branch: &str) {
+        self.awaiting_input = false;
+        self.stack.push(ScriptPoint {
+            tag: branch.to_string(),
+            line: 0,
+        });
+    }
+
+    /// Runs at most one script step. `elapsed` is the time since the last
+    /// call, and counts down any `PauseMs` in progress.
+    pub fn step(
+        &mut self,
+        events: &EventList,
+        world: &mut impl ScriptWorld,
+        elapsed: Duration,
+    ) -> Result<StepOutcome, ScriptError> {
+        if self.awaiting_input {
+            return Ok(StepOutcome::Blocked);
+        }
+        if let Some(delay) = self.delay {
+            if delay > elapsed {
+                self.delay = Some(delay - elapsed);
+                return Ok(StepOutcome::Blocked);
+            }
+            self.delay = None;
+        }
+
+        let stack_entry = match self.stack.pop() {
+            Some(entry) => entry,
+            None => {
+                // Nothing is running - start the next requested event, if any
+                return match self.event_queue.pop_back() {
+                    Some(new_event) => {
+                        self.stack.push(ScriptPoint {
+                            tag: new_event.0,
+                            line: 0,
+                        });
+                        Ok(StepOutcome::Ran(Vec::new()))
+                    }
+                    None => Ok(StepOutcome::Idle),
+                };
+            }
+        };
+
+        let event = events
+            .events
+            .iter()
+            .find(|e| e.tag.eq(&stack_entry.tag))
+            .ok_or_else(|| ScriptError::MissingEvent(stack_entry.tag.clone()))?;
+
+        let mut effects = Vec::new();
+        if stack_entry.line >= event.steps.len() {
+            // We reached the end of the event without a jump
+            return Ok(StepOutcome::Ran(effects));
+        }
+
+        // Put the next step into the stack
+        self.stack.push(ScriptPoint {
+            tag: stack_entry.tag.clone(),
+            line: stack_entry.line + 1,
+        });
+
+        // Execute it
+        match &event.steps[stack_entry.line] {
+            GameEventStep::LogText { text, color } => effects.push(ScriptEffect::Log {
+                text: text.clone(),
+                color: *color,
+            }),
+            GameEventStep::ClearLog => effects.push(ScriptEffect::ClearLog),
+            GameEventStep::PauseMs(ms) => {
+                self.delay = Some(Duration::from_millis(*ms));
+            }
+            GameEventStep::MovePlayer(mv, delay) => {
+                effects.push(ScriptEffect::MovePlayer(*mv));
+                self.delay = Some(Duration::from_millis(*delay));
+            }
+            GameEventStep::ChangeMap { index, x, y } => {
+                effects.push(ScriptEffect::MovePlayer(PlayerMoveRequest::ChangeMap {
+                    index: *index,
+                    x: *x,
+                    y: *y,
+                }));
+            }
+            GameEventStep::CallEvent(tag) => {
+                // The next step in this script is also in the stack, so it'll
+                // resume upon return.
+                self.stack.push(ScriptPoint {
+                    tag: tag.clone(),
+                    line: 0,
+                });
+            }
+            GameEventStep::InputBranch {
+                title,
+                message,
+                portrait,
+                options,
+            } => {
+                self.awaiting_input = true;
+                effects.push(ScriptEffect::AskInput {
+                    title: title.clone(),
+                    message: message.clone(),
+                    portrait: portrait.clone(),
+                    options: options.clone(),
+                });
+            }
+            GameEventStep::Sprite(s) => effects.push(ScriptEffect::Sprite(s.clone())),
+            GameEventStep::Battle(encounter) => {
+                effects.push(ScriptEffect::Battle(encounter.clone()));
+            }
+            GameEventStep::SetFlag { flag, value } => {
+                world.variables().set(flag, GameValue::Flag(*value));
+            }
+            GameEventStep::SetNumber { name, value } => {
+                world.variables().set(name, GameValue::Number(*value));
+            }
+            GameEventStep::SetText { name, value } => {
+                world.variables().set(name, GameValue::Text(value.clone()));
+            }
+            GameEventStep::AddToVar { name, amount } => {
+                world.variables().add(name, *amount);
+            }
+            GameEventStep::IfFlag {
+                flag,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = world.variables().flag(flag);
+                self.push_branch(condition, then_branch, else_branch);
+            }
+            GameEventStep::IfNumber {
+                name,
+                comparison,
+                value,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = comparison.compare(world.variables().number(name), *value);
+                self.push_branch(condition, then_branch, else_branch);
+            }
+            GameEventStep::IfText {
+                name,
+                value,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = world.variables().text(name).eq(value);
+                self.push_branch(condition, then_branch, else_branch);
+            }
+            GameEventStep::GiveItem(item) => effects.push(ScriptEffect::GiveItem(item.clone())),
+            GameEventStep::TakeItem(item) => effects.push(ScriptEffect::TakeItem(item.clone())),
+            GameEventStep::IfHasItem {
+                item,
+                then_branch,
+                else_branch,
+            } => {
+                let condition = world.has_item(item);
+                self.push_branch(condition, then_branch, else_branch);
+            }
+            GameEventStep::SetDoor {
+                x,
+                y,
+                direction,
+                state,
+            } => effects.push(ScriptEffect::SetDoor {
+                x: *x,
+                y: *y,
+                direction: *direction,
+                state: *state,
+            }),
+        }
+
+        Ok(StepOutcome::Ran(effects))
+    }
+
+    /// Jumps to `then_branch` if the condition holds, otherwise to
+    /// `else_branch` (if there is one). Like `CallEvent`, execution resumes
+    /// afterwards.
+    fn push_branch(&mut self, condition: bool, then_branch: &str, else_branch: &Option<String>) {
+        let branch = if condition {
+            Some(then_branch)
+        } else {
+            else_branch.as_deref()
+        };
+        if let Some(tag) = branch {
+            if !tag.is_empty() {
+                self.stack.push(ScriptPoint {
+                    tag: tag.to_string(),
+                    line: 0,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::game_events::GameEvent;
+
+    struct TestWorld {
+        variables: GameVariables,
+        items: Vec<String>,
+    }
+
+    impl ScriptWorld for TestWorld {
+        fn variables(&mut self) -> &mut GameVariables {
+            &mut self.variables
+        }
+
+        fn has_item(&self, tag: &str) -> bool {
+            self.items.iter().any(|i| i == tag)
+        }
+    }
+
+    fn world() -> TestWorld {
+        TestWorld {
+            variables: GameVariables::new(),
+            items: Vec::new(),
+        }
+    }
+
+    fn events(events: Vec<(&str, Vec<GameEventStep>)>) -> EventList {
+        EventList {
+            filename: "test.ron".to_string(),
+            events: events
+                .into_iter()
+                .map(|(tag, steps)| GameEvent {
+                    tag: tag.to_string(),
+                    steps,
+                    filename: "test.ron".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn log(text: &str) -> GameEventStep {
+        GameEventStep::LogText {
+            text: text.to_string(),
+            color: None,
+        }
+    }
+
+    /// Steps until the script stops running, returning the logged text.
+    fn run(state: &mut ScriptState, events: &EventList, world: &mut TestWorld) -> Vec<String> {
+        let mut lines = Vec::new();
+        for _ in 0..1000 {
+            match state.step(events, world, Duration::ZERO).unwrap() {
+                StepOutcome::Ran(effects) => {
+                    for effect in effects {
+                        if let ScriptEffect::Log { text, .. } = effect {
+                            lines.push(text);
+                        }
+                    }
+                }
+                StepOutcome::Idle | StepOutcome::Blocked => return lines,
+            }
+        }
+        panic!("Script never finished");
+    }
+
+    #[test]
+    fn idle_without_events() {
+        let mut state = ScriptState::new();
+        let outcome = state.step(&events(vec![]), &mut world(), Duration::ZERO);
+        assert!(matches!(outcome, Ok(StepOutcome::Idle)));
+    }
+
+    #[test]
+    fn call_event_returns_to_caller() {
+        let events = events(vec![
+            (
+                "Main",
+                vec![
+                    log("before"),
+                    GameEventStep::CallEvent("Sub".to_string()),
+                    log("after"),
+                ],
+            ),
+            ("Sub", vec![log("sub 1"), log("sub 2")]),
+        ]);
+        let mut state = ScriptState::new();
+        state.trigger("Main");
+        let lines = run(&mut state, &events, &mut world());
+        assert_eq!(lines, vec!["before", "sub 1", "sub 2", "after"]);
+    }
+
+    #[test]
+    fn nested_calls_unwind_in_order() {
+        let events = events(vec![
+            (
+                "A",
+                vec![GameEventStep::CallEvent("B".to_string()), log("A")],
+            ),
+            (
+                "B",
+                vec![GameEventStep::CallEvent("C".to_string()), log("B")],
+            ),
+            ("C", vec![log("C")]),
+        ]);
+        let mut state = ScriptState::new();
+        state.trigger("A");
+        let lines = run(&mut state, &events, &mut world());
+        assert_eq!(lines, vec!["C", "B", "A"]);
+    }
+
+    #[test]
+    fn input_branch_blocks_until_chosen() {
+        let events = events(vec![
+            (
+                "Ask",
+                vec![
+                    GameEventStep::InputBranch {
+                        title: "Question".to_string(),
+                        message: "Left or right?".to_string(),
+                        portrait: None,
+                        options: vec![
+                            InputChoice {
+                                branch: "Left".to_string(),
+                                message: "Left".to_string(),
+                            },
+                            InputChoice {
+                                branch: "Right".to_string(),
+                                message: "Right".to_string(),
+                            },
+                        ],
+                    },
+                    log("done"),
+                ],
+            ),
+            ("Left", vec![log("went left")]),
+            ("Right", vec![log("went right")]),
+        ]);
+        let mut state = ScriptState::new();
+        let mut world = world();
+        state.trigger("Ask");
+
+        // Start the event, then hit the InputBranch
+        assert!(matches!(
+            state.step(&events, &mut world, Duration::ZERO),
+            Ok(StepOutcome::Ran(_))
+        ));
+        match state.step(&events, &mut world, Duration::ZERO).unwrap() {
+            StepOutcome::Ran(effects) => match effects.as_slice() {
+                [ScriptEffect::AskInput { options, .. }] => assert_eq!(options.len(), 2),
+                _ => panic!("Expected an input request"),
+            },
+            _ => panic!("Expected the InputBranch to run"),
+        }
+
+        // Nothing happens until the player chooses
+        for _ in 0..10 {
+            assert!(matches!(
+                state.step(&events, &mut world, Duration::from_secs(1)),
+                Ok(StepOutcome::Blocked)
+            ));
+        }
+
+        state.choose_input("Right");
+        let lines = run(&mut state, &events, &mut world);
+        assert_eq!(lines, vec!["went right", "done"]);
+    }
+
+    #[test]
+    fn pause_blocks_for_its_duration() {
+        let events = events(vec![(
+            "Wait",
+            vec![log("start"), GameEventStep::PauseMs(100), log("end")],
+        )]);
+        let mut state = ScriptState::new();
+        let mut world = world();
+        state.trigger("Wait");
+        assert_eq!(run(&mut state, &events, &mut world), vec!["start"]);
+
+        let ms = Duration::from_millis;
+        assert!(matches!(
+            state.step(&events, &mut world, ms(40)),
+            Ok(StepOutcome::Blocked)
+        ));
+        assert!(matches!(
+            state.step(&events, &mut world, ms(40)),
+            Ok(StepOutcome::Blocked)
+        ));
+        // 120ms have now passed, so the script carries on in this step
+        match state.step(&events, &mut world, ms(40)).unwrap() {
+            StepOutcome::Ran(effects) => {
+                assert!(
+                    matches!(effects.as_slice(), [ScriptEffect::Log { text, .. }] if text == "end")
+                )
+            }
+            _ => panic!("Expected the pause to have finished"),
+        }
+    }
+
+    #[test]
+    fn missing_event_is_an_error() {
+        let events = events(vec![(
+            "Main",
+            vec![
+                GameEventStep::CallEvent("Nowhere".to_string()),
+                log("after"),
+            ],
+        )]);
+        let mut state = ScriptState::new();
+        let mut world = world();
+        state.trigger("Main");
+
+        let mut error = None;
+        for _ in 0..10 {
+            match state.step(&events, &mut world, Duration::ZERO) {
+                Err(e) => {
+                    error = Some(e);
+                    break;
+                }
+                Ok(StepOutcome::Idle) => break,
+                Ok(_) => {}
+            }
+        }
+        assert_eq!(
+            error,
+            Some(ScriptError::MissingEvent("Nowhere".to_string()))
+        );
+
+        // The caller carries on after the broken call
+        assert_eq!(run(&mut state, &events, &mut world), vec!["after"]);
+    }
+
+    #[test]
+    fn missing_triggered_event_is_an_error() {
+        let mut state = ScriptState::new();
+        let mut world = world();
+        state.trigger("Ghost");
+        let events = events(vec![]);
+        assert!(matches!(
+            state.step(&events, &mut world, Duration::ZERO),
+            Ok(StepOutcome::Ran(_))
+        ));
+        assert!(matches!(
+            state.step(&events, &mut world, Duration::ZERO),
+            Err(ScriptError::MissingEvent(tag)) if tag == "Ghost"
+        ));
+        assert!(matches!(
+            state.step(&events, &mut world, Duration::ZERO),
+            Ok(StepOutcome::Idle)
+        ));
+    }
+
+    #[test]
+    fn conditions_read_the_world() {
+        let events = events(vec![
+            (
+                "Main",
+                vec![
+                    GameEventStep::SetNumber {
+                        name: "gold".to_string(),
+                        value: 5,
+                    },
+                    GameEventStep::IfHasItem {
+                        item: "Key".to_string(),
+                        then_branch: "HasKey".to_string(),
+                        else_branch: Some("NoKey".to_string()),
+                    },
+                ],
+            ),
+            ("HasKey", vec![log("has key")]),
+            ("NoKey", vec![log("no key")]),
+        ]);
+        let mut state = ScriptState::new();
+        let mut world = world();
+        world.items.push("Key".to_string());
+        state.trigger("Main");
+        assert_eq!(run(&mut state, &events, &mut world), vec!["has key"]);
+        assert_eq!(world.variables.number("gold"), 5);
+    }
+}
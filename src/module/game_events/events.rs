@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-use crate::game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest};
+use super::{Comparison, SCRIPT_EXTENSION};
+use crate::{
+    game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest},
+    module::Direction,
+    region::region_map::DoorState,
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct EventList {
@@ -12,6 +17,17 @@ pub struct EventList {
 pub struct GameEvent {
     pub tag: String,
     pub steps: Vec<GameEventStep>,
+    /// The script file (inside `scripts/`) this event is stored in
+    #[serde(skip)]
+    pub filename: String,
+}
+
+impl GameEvent {
+    /// Events written in a `.pbs` script. Saving doesn't write scripts
+    /// back, so the editor leaves these alone.
+    pub fn is_read_only(&self) -> bool {
+        self.filename.ends_with(&format!(".{}", SCRIPT_EXTENSION))
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -42,7 +58,55 @@ pub enum GameEventStep {
         y: u32,
     },
     Sprite(SpriteRequest),
-    Battle,
+    Battle(String),
+    SetFlag {
+        flag: String,
+        value: bool,
+    },
+    SetNumber {
+        name: String,
+        value: i32,
+    },
+    SetText {
+        name: String,
+        value: String,
+    },
+    AddToVar {
+        name: String,
+        amount: i32,
+    },
+    IfFlag {
+        flag: String,
+        then_branch: String,
+        else_branch: Option<String>,
+    },
+    IfNumber {
+        name: String,
+        comparison: Comparison,
+        value: i32,
+        then_branch: String,
+        else_branch: Option<String>,
+    },
+    IfText {
+        name: String,
+        value: String,
+        then_branch: String,
+        else_branch: Option<String>,
+    },
+    GiveItem(String),
+    TakeItem(String),
+    IfHasItem {
+        item: String,
+        then_branch: String,
+        else_branch: Option<String>,
+    },
+    /// Opens, closes or locks a door on the current map
+    SetDoor {
+        x: u32,
+        y: u32,
+        direction: Direction,
+        state: DoorState,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -52,4 +116,178 @@ pub enum EventPicker {
     PauseMs,
     CallEvent,
     MovePlayer,
+    InputBranch,
+    ChangeMap,
+    Sprite,
+    Battle,
+    SetFlag,
+    SetNumber,
+    SetText,
+    AddToVar,
+    IfFlag,
+    IfNumber,
+    IfText,
+    GiveItem,
+    TakeItem,
+    IfHasItem,
+    SetDoor,
+}
+
+impl EventPicker {
+    pub const ALL: [EventPicker; 20] = [
+        EventPicker::LogText,
+        EventPicker::ClearLog,
+        EventPicker::PauseMs,
+        EventPicker::CallEvent,
+        EventPicker::MovePlayer,
+        EventPicker::InputBranch,
+        EventPicker::ChangeMap,
+        EventPicker::Sprite,
+        EventPicker::Battle,
+        EventPicker::SetFlag,
+        EventPicker::SetNumber,
+        EventPicker::SetText,
+        EventPicker::AddToVar,
+        EventPicker::IfFlag,
+        EventPicker::IfNumber,
+        EventPicker::IfText,
+        EventPicker::GiveItem,
+        EventPicker::TakeItem,
+        EventPicker::IfHasItem,
+        EventPicker::SetDoor,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventPicker::LogText => "Log",
+            EventPicker::ClearLog => "Clear Log",
+            EventPicker::PauseMs => "Pause Delay MS",
+            EventPicker::CallEvent => "Call",
+            EventPicker::MovePlayer => "Move Player",
+            EventPicker::InputBranch => "Input Branch",
+            EventPicker::ChangeMap => "Change Map",
+            EventPicker::Sprite => "Sprite",
+            EventPicker::Battle => "Battle",
+            EventPicker::SetFlag => "Set Flag",
+            EventPicker::SetNumber => "Set Number",
+            EventPicker::SetText => "Set Text",
+            EventPicker::AddToVar => "Add To Variable",
+            EventPicker::IfFlag => "If Flag",
+            EventPicker::IfNumber => "If Number",
+            EventPicker::IfText => "If Text",
+            EventPicker::GiveItem => "Give Item",
+            EventPicker::TakeItem => "Take Item",
+            EventPicker::IfHasItem => "If Has Item",
+            EventPicker::SetDoor => "Set Door",
+        }
+    }
+
+    /// A step of this kind, filled in with placeholder values.
+    pub fn new_step(&self) -> GameEventStep {
+        match self {
+            EventPicker::LogText => GameEventStep::LogText {
+                text: "Hello".to_string(),
+                color: None,
+            },
+            EventPicker::ClearLog => GameEventStep::ClearLog,
+            EventPicker::PauseMs => GameEventStep::PauseMs(33),
+            EventPicker::CallEvent => GameEventStep::CallEvent(String::new()),
+            EventPicker::MovePlayer => GameEventStep::MovePlayer(PlayerMoveRequest::Forwards, 1000),
+            EventPicker::InputBranch => GameEventStep::InputBranch {
+                title: "Question".to_string(),
+                message: String::new(),
+                portrait: None,
+                options: vec![InputChoice {
+                    branch: String::new(),
+                    message: "OK".to_string(),
+                }],
+            },
+            EventPicker::ChangeMap => GameEventStep::ChangeMap {
+                index: 0,
+                x: 0,
+                y: 0,
+            },
+            EventPicker::Sprite => GameEventStep::Sprite(SpriteRequest::Spawn {
+                id: String::new(),
+                position: (0, 0),
+                image: String::new(),
+            }),
+            EventPicker::Battle => GameEventStep::Battle(String::new()),
+            EventPicker::SetFlag => GameEventStep::SetFlag {
+                flag: String::new(),
+                value: true,
+            },
+            EventPicker::SetNumber => GameEventStep::SetNumber {
+                name: String::new(),
+                value: 0,
+            },
+            EventPicker::SetText => GameEventStep::SetText {
+                name: String::new(),
+                value: String::new(),
+            },
+            EventPicker::AddToVar => GameEventStep::AddToVar {
+                name: String::new(),
+                amount: 1,
+            },
+            EventPicker::IfFlag => GameEventStep::IfFlag {
+                flag: String::new(),
+                then_branch: String::new(),
+                else_branch: None,
+            },
+            EventPicker::IfNumber => GameEventStep::IfNumber {
+                name: String::new(),
+                comparison: Comparison::Equal,
+                value: 0,
+                then_branch: String::new(),
+                else_branch: None,
+            },
+            EventPicker::IfText => GameEventStep::IfText {
+                name: String::new(),
+                value: String::new(),
+                then_branch: String::new(),
+                else_branch: None,
+            },
+            EventPicker::GiveItem => GameEventStep::GiveItem(String::new()),
+            EventPicker::TakeItem => GameEventStep::TakeItem(String::new()),
+            EventPicker::IfHasItem => GameEventStep::IfHasItem {
+                item: String::new(),
+                then_branch: String::new(),
+                else_branch: None,
+            },
+            EventPicker::SetDoor => GameEventStep::SetDoor {
+                x: 0,
+                y: 0,
+                direction: Direction::North,
+                state: DoorState::Open,
+            },
+        }
+    }
+}
+
+impl GameEventStep {
+    /// Which kind of step this is.
+    pub fn picker(&self) -> EventPicker {
+        match self {
+            GameEventStep::LogText { .. } => EventPicker::LogText,
+            GameEventStep::ClearLog => EventPicker::ClearLog,
+            GameEventStep::PauseMs(..) => EventPicker::PauseMs,
+            GameEventStep::CallEvent(..) => EventPicker::CallEvent,
+            GameEventStep::MovePlayer(..) => EventPicker::MovePlayer,
+            GameEventStep::InputBranch { .. } => EventPicker::InputBranch,
+            GameEventStep::ChangeMap { .. } => EventPicker::ChangeMap,
+            GameEventStep::Sprite(..) => EventPicker::Sprite,
+            GameEventStep::Battle(..) => EventPicker::Battle,
+            GameEventStep::SetFlag { .. } => EventPicker::SetFlag,
+            GameEventStep::SetNumber { .. } => EventPicker::SetNumber,
+            GameEventStep::SetText { .. } => EventPicker::SetText,
+            GameEventStep::AddToVar { .. } => EventPicker::AddToVar,
+            GameEventStep::IfFlag { .. } => EventPicker::IfFlag,
+            GameEventStep::IfNumber { .. } => EventPicker::IfNumber,
+            GameEventStep::IfText { .. } => EventPicker::IfText,
+            GameEventStep::GiveItem(..) => EventPicker::GiveItem,
+            GameEventStep::TakeItem(..) => EventPicker::TakeItem,
+            GameEventStep::IfHasItem { .. } => EventPicker::IfHasItem,
+            GameEventStep::SetDoor { .. } => EventPicker::SetDoor,
+        }
+    }
 }
@@ -1,4 +1,10 @@
+mod engine;
+pub use engine::*;
 mod events;
 pub use events::*;
 mod runner;
 pub use runner::*;
+mod script;
+pub use script::*;
+mod variables;
+pub use variables::*;
@@ -0,0 +1,708 @@
+//! `.pbs` scripts: a plain text way of writing events. Each event starts
+//! with a label, followed by one step per line:
+//!
+//! ```text
+//! # Comments run to the end of the line
+//! Intro:
+//!     say "Welcome to the keep." color 255,200,0
+//!     pause 500
+//!     choice "Gate" "Go inside?" portrait "guard.png"
+//!         "Yes" -> EnterKeep
+//!         "No" -> StayOutside
+//!     end
+//!     if number Visits >= 3 then Regular else Stranger
+//!     goto map 0 at 15,4
+//! ```
+
+use super::{Comparison, GameEvent, GameEventStep, InputChoice};
+use crate::{
+    game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest},
+    module::Direction,
+    region::region_map::DoorState,
+};
+use std::{fmt, str::FromStr};
+
+/// Extension of script files in a module's `scripts/` directory.
+pub const SCRIPT_EXTENSION: &str = "pbs";
+
+/// A script that could not be read, with the 1-based line and column of
+/// the problem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ScriptParseError {}
+
+/// Compiles script source into events. Event filenames are left empty.
+pub fn parse_script(source: &str) -> Result<Vec<GameEvent>, ScriptParseError> {
+    let mut lines = Vec::new();
+    for (i, text) in source.lines().enumerate() {
+        let line = Line::new(text, i + 1)?;
+        if !line.tokens.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    let mut events: Vec<GameEvent> = Vec::new();
+    let mut lines = lines.into_iter();
+    while let Some(mut line) = lines.next() {
+        if let Some(tag) = line.label() {
+            if events.iter().any(|e| e.tag == tag) {
+                let column = line.column();
+                return Err(line.error_at(column, format!("Event '{}' is already defined", tag)));
+            }
+            events.push(GameEvent {
+                tag,
+                steps: Vec::new(),
+                filename: String::new(),
+            });
+            continue;
+        }
+
+        if events.is_empty() {
+            let column = line.column();
+            return Err(line.error_at(column, "Steps must follow a label such as 'Intro:'"));
+        }
+        let step = if line.peek_word() == Some("choice") {
+            choice(&mut line, &mut lines)?
+        } else {
+            step(&mut line)?
+        };
+        if let Some(event) = events.last_mut() {
+            event.steps.push(step);
+        }
+    }
+    Ok(events)
+}
+
+fn step(line: &mut Line) -> Result<GameEventStep, ScriptParseError> {
+    let command = line.word("a command")?;
+    let step = match command.as_str() {
+        "say" => {
+            let text = line.text("the text to show")?;
+            let color = if line.optional("color") {
+                let r = line.number("a red value")?;
+                line.symbol(",")?;
+                let g = line.number("a green value")?;
+                line.symbol(",")?;
+                let b = line.number("a blue value")?;
+                Some((r, g, b))
+            } else {
+                None
+            };
+            GameEventStep::LogText { text, color }
+        }
+        "clear" => GameEventStep::ClearLog,
+        "pause" => GameEventStep::PauseMs(line.number("a time in milliseconds")?),
+        "call" => GameEventStep::CallEvent(line.name("an event tag")?),
+        "move" => {
+            let request = match line.word("forward, back, left or right")?.as_str() {
+                "forward" => PlayerMoveRequest::Forwards,
+                "back" => PlayerMoveRequest::Backwards,
+                "left" => PlayerMoveRequest::TurnLeft,
+                "right" => PlayerMoveRequest::TurnRight,
+                other => {
+                    return Err(line.previous_error(expected("forward, back, left or right", other)))
+                }
+            };
+            let delay = if line.is_finished() {
+                0
+            } else {
+                line.number("a delay in milliseconds")?
+            };
+            GameEventStep::MovePlayer(request, delay)
+        }
+        "goto" => {
+            line.keyword("map")?;
+            let index = line.number("a map index")?;
+            line.keyword("at")?;
+            let (x, y) = line.position()?;
+            GameEventStep::ChangeMap { index, x, y }
+        }
+        "battle" => GameEventStep::Battle(line.name("an encounter tag")?),
+        "set" => match line.word("flag, number or text")?.as_str() {
+            "flag" => {
+                let flag = line.name("a flag name")?;
+                let value = match line.word("true or false")?.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    other => return Err(line.previous_error(expected("true or false", other))),
+                };
+                GameEventStep::SetFlag { flag, value }
+            }
+            "number" => {
+                let name = line.name("a variable name")?;
+                let value = line.number("a number")?;
+                GameEventStep::SetNumber { name, value }
+            }
+            "text" => {
+                let name = line.name("a variable name")?;
+                let value = line.text("the text to store")?;
+                GameEventStep::SetText { name, value }
+            }
+            other => return Err(line.previous_error(expected("flag, number or text", other))),
+        },
+        "add" => {
+            let name = line.name("a variable name")?;
+            let amount = line.number("an amount")?;
+            GameEventStep::AddToVar { name, amount }
+        }
+        "if" => condition(line)?,
+        "give" => GameEventStep::GiveItem(line.name("an item tag")?),
+        "take" => GameEventStep::TakeItem(line.name("an item tag")?),
+        "door" => {
+            let (x, y) = line.position()?;
+            let direction = direction(line)?;
+            let state = match line.word("open, closed or locked")?.as_str() {
+                "open" => DoorState::Open,
+                "closed" => DoorState::Closed,
+                "locked" => DoorState::Locked,
+                other => return Err(line.previous_error(expected("open, closed or locked", other))),
+            };
+            GameEventStep::SetDoor {
+                x,
+                y,
+                direction,
+                state,
+            }
+        }
+        "sprite" => GameEventStep::Sprite(sprite(line)?),
+        "end" => return Err(line.previous_error("'end' without a choice")),
+        other => return Err(line.previous_error(format!("Unknown command '{}'", other))),
+    };
+    line.finish()?;
+    Ok(step)
+}
+
+/// `if flag|number|text|item ... then Tag [else Tag]`
+fn condition(line: &mut Line) -> Result<GameEventStep, ScriptParseError> {
+    let kind = line.word("flag, number, text or item")?;
+    let step = match kind.as_str() {
+        "flag" => {
+            let flag = line.name("a flag name")?;
+            let (then_branch, else_branch) = branches(line)?;
+            GameEventStep::IfFlag {
+                flag,
+                then_branch,
+                else_branch,
+            }
+        }
+        "number" => {
+            let name = line.name("a variable name")?;
+            let comparison = comparison(line)?;
+            let value = line.number("a number")?;
+            let (then_branch, else_branch) = branches(line)?;
+            GameEventStep::IfNumber {
+                name,
+                comparison,
+                value,
+                then_branch,
+                else_branch,
+            }
+        }
+        "text" => {
+            let name = line.name("a variable name")?;
+            let value = line.text("the text to compare with")?;
+            let (then_branch, else_branch) = branches(line)?;
+            GameEventStep::IfText {
+                name,
+                value,
+                then_branch,
+                else_branch,
+            }
+        }
+        "item" => {
+            let item = line.name("an item tag")?;
+            let (then_branch, else_branch) = branches(line)?;
+            GameEventStep::IfHasItem {
+                item,
+                then_branch,
+                else_branch,
+            }
+        }
+        other => return Err(line.previous_error(expected("flag, number, text or item", other))),
+    };
+    Ok(step)
+}
+
+fn branches(line: &mut Line) -> Result<(String, Option<String>), ScriptParseError> {
+    line.keyword("then")?;
+    let then_branch = line.name("an event tag")?;
+    let else_branch = if line.optional("else") {
+        Some(line.name("an event tag")?)
+    } else {
+        None
+    };
+    Ok((then_branch, else_branch))
+}
+
+fn comparison(line: &mut Line) -> Result<Comparison, ScriptParseError> {
+    let column = line.column();
+    if let Some(Token::Symbol(symbol)) = line.next() {
+        if let Some(c) = Comparison::ALL.iter().find(|c| c.symbol() == symbol) {
+            return Ok(*c);
+        }
+    }
+    Err(line.error_at(column, "Expected a comparison such as == or >="))
+}
+
+fn direction(line: &mut Line) -> Result<Direction, ScriptParseError> {
+    match line.word("north, south, east or west")?.as_str() {
+        "north" => Ok(Direction::North),
+        "south" => Ok(Direction::South),
+        "east" => Ok(Direction::East),
+        "west" => Ok(Direction::West),
+        other => Err(line.previous_error(expected("north, south, east or west", other))),
+    }
+}
+
+fn sprite(line: &mut Line) -> Result<SpriteRequest, ScriptParseError> {
+    let action = line.word("spawn, remove, move or walk")?;
+    let request = match action.as_str() {
+        "spawn" => {
+            let id = line.name("a sprite id")?;
+            line.keyword("at")?;
+            let position = line.position()?;
+            line.keyword("image")?;
+            let image = line.text("an image filename")?;
+            SpriteRequest::Spawn {
+                id,
+                position,
+                image,
+            }
+        }
+        "remove" => SpriteRequest::Remove {
+            id: line.name("a sprite id")?,
+        },
+        "move" => {
+            let id = line.name("a sprite id")?;
+            line.keyword("to")?;
+            let position = line.position()?;
+            SpriteRequest::Move { id, position }
+        }
+        "walk" => {
+            let id = line.name("a sprite id")?;
+            line.keyword("to")?;
+            let destination = line.position()?;
+            let step_ms = if line.optional("every") {
+                let step_ms = line.number("a time in milliseconds")?;
+                if step_ms == 0 {
+                    return Err(line.previous_error("Sprites can't walk a step every 0ms"));
+                }
+                step_ms
+            } else {
+                250
+            };
+            SpriteRequest::Walk {
+                id,
+                destination,
+                step_ms,
+            }
+        }
+        other => return Err(line.previous_error(expected("spawn, remove, move or walk", other))),
+    };
+    Ok(request)
+}
+
+/// `choice "Title" "Message" [portrait "image"]`, then one `"Option" -> Tag`
+/// line per option, then `end`.
+fn choice(
+    line: &mut Line,
+    lines: &mut impl Iterator<Item = Line>,
+) -> Result<GameEventStep, ScriptParseError> {
+    let column = line.column();
+    line.keyword("choice")?;
+    let title = line.text("a title")?;
+    let message = line.text("a message")?;
+    let portrait = if line.optional("portrait") {
+        Some(line.text("an image filename")?)
+    } else {
+        None
+    };
+    line.finish()?;
+
+    let mut options = Vec::new();
+    loop {
+        let mut option = match lines.next() {
+            Some(option) => option,
+            None => return Err(line.error_at(column, "This choice is missing its 'end'")),
+        };
+        if option.peek_word() == Some("end") {
+            option.next();
+            option.finish()?;
+            break;
+        }
+        let message = option.text("an option such as \"Yes\" -> Tag")?;
+        option.symbol("->")?;
+        let branch = option.name("an event tag")?;
+        option.finish()?;
+        options.push(InputChoice { branch, message });
+    }
+    if options.is_empty() {
+        return Err(line.error_at(column, "A choice needs at least one option"));
+    }
+
+    Ok(GameEventStep::InputBranch {
+        title,
+        message,
+        portrait,
+        options,
+    })
+}
+
+fn expected(what: &str, found: &str) -> String {
+    format!("Expected {}, found '{}'", what, found)
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{}'", word),
+            Token::Text(text) => format!("\"{}\"", text),
+            Token::Symbol(symbol) => format!("'{}'", symbol),
+        }
+    }
+}
+
+/// Longest first, so "<=" isn't read as "<".
+const SYMBOLS: [&str; 9] = ["->", "==", "!=", "<=", ">=", "<", ">", ",", ":"];
+
+/// One line of a script, split into tokens and read from left to right.
+struct Line {
+    number: usize,
+    /// Token and the column it starts at
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    end_column: usize,
+}
+
+impl Line {
+    fn new(text: &str, number: usize) -> Result<Self, ScriptParseError> {
+        let chars: Vec<char> = text.chars().collect();
+        let error = |column: usize, message: String| ScriptParseError {
+            line: number,
+            column,
+            message,
+        };
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let column = i + 1;
+            if c == '#' {
+                break;
+            } else if c.is_whitespace() {
+                i += 1;
+            } else if c == '"' {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error(column, "Unterminated string".to_string())),
+                        Some('"') => break,
+                        Some('\\') => {
+                            match chars.get(i + 1) {
+                                Some('n') => text.push('\n'),
+                                Some('"') => text.push('"'),
+                                Some('\\') => text.push('\\'),
+                                _ => {
+                                    return Err(error(i + 1, "Unknown escape sequence".to_string()))
+                                }
+                            }
+                            i += 1;
+                        }
+                        Some(c) => text.push(*c),
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push((Token::Text(text), column));
+            } else if let Some(symbol) = SYMBOLS.iter().find(|s| {
+                s.chars()
+                    .enumerate()
+                    .all(|(n, sc)| chars.get(i + n) == Some(&sc))
+            }) {
+                i += symbol.len();
+                tokens.push((Token::Symbol(*symbol), column));
+            } else if is_word_char(c) {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push((Token::Word(chars[start..i].iter().collect()), column));
+            } else {
+                return Err(error(column, format!("Unexpected character '{}'", c)));
+            }
+        }
+        Ok(Self {
+            number,
+            tokens,
+            position: 0,
+            end_column: chars.len() + 1,
+        })
+    }
+
+    /// `Tag:` on a line of its own starts a new event. Tags with spaces
+    /// are quoted: `"Buy Potion":`.
+    fn label(&self) -> Option<String> {
+        match self.tokens.as_slice() {
+            [(Token::Word(tag), _), (Token::Symbol(":"), _)]
+            | [(Token::Text(tag), _), (Token::Symbol(":"), _)] => Some(tag.clone()),
+            _ => None,
+        }
+    }
+
+    fn error_at(&self, column: usize, message: impl Into<String>) -> ScriptParseError {
+        ScriptParseError {
+            line: self.number,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error pointing at the token that was just read.
+    fn previous_error(&self, message: impl Into<String>) -> ScriptParseError {
+        let column = self.tokens[self.position.saturating_sub(1)].1;
+        self.error_at(column, message)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end_column, |(_, column)| *column)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).map(|(t, _)| t.clone());
+        self.position += 1;
+        token
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.tokens.get(self.position) {
+            Some((Token::Word(word), _)) => Some(word.as_str()),
+            _ => None,
+        }
+    }
+
+    fn expect<T>(
+        &mut self,
+        what: &str,
+        read: impl FnOnce(&Token) -> Option<T>,
+    ) -> Result<T, ScriptParseError> {
+        let column = self.column();
+        match self.next() {
+            Some(token) => match read(&token) {
+                Some(value) => Ok(value),
+                None => Err(self.error_at(column, expected(what, &token.describe()))),
+            },
+            None => Err(self.error_at(column, format!("Expected {}", what))),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<String, ScriptParseError> {
+        self.expect(what, |t| match t {
+            Token::Word(word) => Some(word.clone()),
+            _ => None,
+        })
+    }
+
+    fn text(&mut self, what: &str) -> Result<String, ScriptParseError> {
+        self.expect(what, |t| match t {
+            Token::Text(text) => Some(text.clone()),
+            _ => None,
+        })
+    }
+
+    /// A tag or variable name, quoted if it has spaces in it.
+    fn name(&mut self, what: &str) -> Result<String, ScriptParseError> {
+        self.expect(what, |t| match t {
+            Token::Word(name) | Token::Text(name) => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    fn number<T: FromStr>(&mut self, what: &str) -> Result<T, ScriptParseError> {
+        self.expect(what, |t| match t {
+            Token::Word(word) => word.parse().ok(),
+            _ => None,
+        })
+    }
+
+    fn symbol(&mut self, symbol: &str) -> Result<(), ScriptParseError> {
+        self.expect(&format!("'{}'", symbol), |t| match t {
+            Token::Symbol(s) if *s == symbol => Some(()),
+            _ => None,
+        })
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), ScriptParseError> {
+        self.expect(&format!("'{}'", keyword), |t| match t {
+            Token::Word(word) if word == keyword => Some(()),
+            _ => None,
+        })
+    }
+
+    /// Reads `keyword` if it comes next.
+    fn optional(&mut self, keyword: &str) -> bool {
+        if self.peek_word() == Some(keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `x,y`
+    fn position(&mut self) -> Result<(u32, u32), ScriptParseError> {
+        let x = self.number("an x position")?;
+        self.symbol(",")?;
+        let y = self.number("a y position")?;
+        Ok((x, y))
+    }
+
+    fn finish(&self) -> Result<(), ScriptParseError> {
+        match self.tokens.get(self.position) {
+            Some((token, column)) => {
+                Err(self.error_at(*column, format!("Unexpected {}", token.describe())))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+# The gate of the keep
+Intro:
+    say "Welcome." color 255,200,0
+    pause 500
+    choice "Gate" "Go inside?" portrait "guard.png"
+        "Yes" -> EnterKeep
+        "No, \"later\"" -> StayOutside
+    end
+    if number Visits >= 3 then Regular else Stranger
+
+EnterKeep:
+    goto map 2 at 15,4
+    sprite walk guard to 3,4 every 100
+    door 1,2 north locked
+"#;
+
+    #[test]
+    fn parses_events_and_steps() {
+        let events = parse_script(SCRIPT).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].tag, "Intro");
+        assert_eq!(events[0].steps.len(), 4);
+        assert!(matches!(
+            &events[0].steps[0],
+            GameEventStep::LogText { text, color: Some((255, 200, 0)) } if text == "Welcome."
+        ));
+        assert!(matches!(events[0].steps[1], GameEventStep::PauseMs(500)));
+        match &events[0].steps[2] {
+            GameEventStep::InputBranch {
+                portrait, options, ..
+            } => {
+                assert_eq!(portrait.as_deref(), Some("guard.png"));
+                assert_eq!(options.len(), 2);
+                assert_eq!(options[1].message, "No, \"later\"");
+                assert_eq!(options[1].branch, "StayOutside");
+            }
+            _ => panic!("expected a choice"),
+        }
+        assert!(matches!(
+            &events[0].steps[3],
+            GameEventStep::IfNumber {
+                comparison: Comparison::GreaterOrEqual,
+                value: 3,
+                else_branch: Some(_),
+                ..
+            }
+        ));
+
+        assert!(matches!(
+            events[1].steps[0],
+            GameEventStep::ChangeMap {
+                index: 2,
+                x: 15,
+                y: 4
+            }
+        ));
+        assert!(matches!(
+            &events[1].steps[1],
+            GameEventStep::Sprite(SpriteRequest::Walk {
+                destination: (3, 4),
+                step_ms: 100,
+                ..
+            })
+        ));
+        assert!(matches!(
+            events[1].steps[2],
+            GameEventStep::SetDoor {
+                direction: Direction::North,
+                state: DoorState::Locked,
+                ..
+            }
+        ));
+    }
+
+    fn error(source: &str) -> (usize, usize) {
+        let e = parse_script(source).err().unwrap();
+        (e.line, e.column)
+    }
+
+    #[test]
+    fn errors_have_line_and_column() {
+        assert_eq!(error("Intro:\n  dance"), (2, 3));
+        assert_eq!(error("Intro:\n  say \"Hello"), (2, 7));
+        assert_eq!(error("Intro:\n  pause soon"), (2, 9));
+        assert_eq!(error("Intro:\n  goto map 0 at 1,2 now"), (2, 21));
+        assert_eq!(error("  say \"Hello\""), (1, 3));
+        assert_eq!(error("A:\n  A:"), (2, 3));
+        assert_eq!(error("A:\n  sprite walk guard to 3,4 every 0"), (2, 34));
+    }
+
+    #[test]
+    fn quoted_labels_can_be_called() {
+        let events =
+            parse_script("\"Buy Potion\":\n  clear\nShop:\n  call \"Buy Potion\"").unwrap();
+        assert_eq!(events[0].tag, "Buy Potion");
+        assert!(
+            matches!(&events[1].steps[0], GameEventStep::CallEvent(tag) if tag == "Buy Potion")
+        );
+    }
+
+    #[test]
+    fn choice_needs_an_end() {
+        let e = parse_script("Intro:\n  choice \"Title\" \"Message\"\n  \"Yes\" -> Yes").err();
+        assert_eq!(e.map(|e| (e.line, e.column)), Some((2, 3)));
+    }
+}
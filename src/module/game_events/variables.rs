@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A value stored in a module-scoped script variable.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameValue {
+    Flag(bool),
+    Number(i32),
+    Text(String),
+}
+
+/// Variables set by scripts during a play session. Missing variables
+/// read as false, zero or an empty string.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GameVariables {
+    values: HashMap<String, GameValue>,
+}
+
+impl GameVariables {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: GameValue) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&GameValue> {
+        self.values.get(name)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        match self.values.get(name) {
+            Some(GameValue::Flag(b)) => *b,
+            Some(GameValue::Number(n)) => *n != 0,
+            Some(GameValue::Text(s)) => !s.is_empty(),
+            None => false,
+        }
+    }
+
+    pub fn number(&self, name: &str) -> i32 {
+        match self.values.get(name) {
+            Some(GameValue::Number(n)) => *n,
+            Some(GameValue::Flag(true)) => 1,
+            Some(GameValue::Text(s)) => s.parse().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> String {
+        match self.values.get(name) {
+            Some(GameValue::Text(s)) => s.clone(),
+            Some(GameValue::Number(n)) => n.to_string(),
+            Some(GameValue::Flag(b)) => b.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// Adds `amount` to a numeric variable, treating it as zero if unset.
+    pub fn add(&mut self, name: &str, amount: i32) {
+        let n = self.number(name);
+        self.set(name, GameValue::Number(n.saturating_add(amount)));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 6] = [
+        Comparison::Equal,
+        Comparison::NotEqual,
+        Comparison::Less,
+        Comparison::LessOrEqual,
+        Comparison::Greater,
+        Comparison::GreaterOrEqual,
+    ];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+
+    pub fn compare(self, lhs: i32, rhs: i32) -> bool {
+        match self {
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+            Comparison::Less => lhs < rhs,
+            Comparison::LessOrEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::Dice;
+use serde::{Deserialize, Serialize};
+
+/// Something the party can carry, defined by the module.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ItemTemplate {
+    pub tag: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub kind: ItemKind,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ItemKind {
+    /// Weapons with a range above 1 go in the ranged slot.
+    Weapon {
+        damage: Dice,
+        range: i32,
+    },
+    Armor {
+        slot: EquipSlot,
+        armor_class: i32,
+    },
+    /// Used up when drunk/read/eaten, restoring hit points.
+    Consumable {
+        heal: Dice,
+    },
+    /// Plot items: keys, letters, the MacGuffin.
+    Quest,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum EquipSlot {
+    Melee,
+    Ranged,
+    Body,
+    Shield,
+    Head,
+}
+
+impl EquipSlot {
+    pub const ALL: [EquipSlot; 5] = [
+        EquipSlot::Melee,
+        EquipSlot::Ranged,
+        EquipSlot::Body,
+        EquipSlot::Shield,
+        EquipSlot::Head,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EquipSlot::Melee => "Melee",
+            EquipSlot::Ranged => "Ranged",
+            EquipSlot::Body => "Body",
+            EquipSlot::Shield => "Shield",
+            EquipSlot::Head => "Head",
+        }
+    }
+}
+
+impl ItemTemplate {
+    /// Where the item is worn or wielded, if it can be equipped at all.
+    pub fn slot(&self) -> Option<EquipSlot> {
+        match &self.kind {
+            ItemKind::Weapon { range, .. } if *range > 1 => Some(EquipSlot::Ranged),
+            ItemKind::Weapon { .. } => Some(EquipSlot::Melee),
+            ItemKind::Armor { slot, .. } => Some(*slot),
+            _ => None,
+        }
+    }
+}
+
+/// The contents of a file in a module's `items/` directory.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ItemFile {
+    pub items: Vec<ItemTemplate>,
+}
@@ -4,4 +4,10 @@ mod materials;
 pub use materials::{default_pbr, MaterialDefinition};
 mod direction;
 pub use direction::Direction;
+mod dice;
 pub mod game_events;
+pub use dice::Dice;
+mod monsters;
+pub use monsters::*;
+mod items;
+pub use items::*;
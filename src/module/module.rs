@@ -1,5 +1,12 @@
-use super::{game_events::EventList, MaterialDefinition};
-use crate::region::region_map::RegionMap;
+use super::Direction;
+use super::{
+    game_events::{EventList, GameEventStep},
+    Encounter, ItemTemplate, MaterialDefinition, MonsterTemplate,
+};
+use crate::{
+    game_states::player_movement::PlayerMoveRequest,
+    region::region_map::{Anchor, RegionMap},
+};
 use std::collections::HashMap;
 
 /// Represents an adventure module, bundling all assets together.
@@ -18,6 +25,9 @@ pub struct Module {
     pub base_path: String,
     pub ui_images: Vec<(String, String)>,
     pub sprites: Vec<(String, String)>,
+    pub monsters: HashMap<String, MonsterTemplate>,
+    pub encounters: HashMap<String, Encounter>,
+    pub items: HashMap<String, ItemTemplate>,
 }
 
 impl Module {
@@ -61,10 +71,345 @@ impl Module {
             base_path: "./modules/NewModule".to_string(),
             ui_images: Vec::new(),
             sprites: Vec::new(),
+            monsters: HashMap::new(),
+            encounters: HashMap::new(),
+            items: HashMap::new(),
         }
     }
 
+    /// Display name for an item tag, falling back to the tag itself.
+    pub fn item_name(&self, tag: &str) -> String {
+        self.items
+            .get(tag)
+            .map_or_else(|| tag.to_string(), |item| item.name.clone())
+    }
+
+    /// Resizes a map, moving scripted map changes that arrive on it along
+    /// with its tiles.
+    pub fn resize_map(&mut self, map_idx: usize, size: (u32, u32), anchor: (Anchor, Anchor)) {
+        let map = match self.maps.get_mut(&map_idx) {
+            Some(map) => map,
+            None => return,
+        };
+        let (dx, dy) = map.resize(size, anchor);
+        let size = map.size;
+        let shift = |x: &mut u32, y: &mut u32| {
+            *x = (*x as i32 + dx).clamp(0, size.0 as i32 - 1) as u32;
+            *y = (*y as i32 + dy).clamp(0, size.1 as i32 - 1) as u32;
+        };
+        for event in self.events.events.iter_mut() {
+            for step in event.steps.iter_mut() {
+                match step {
+                    GameEventStep::ChangeMap { index, x, y }
+                    | GameEventStep::MovePlayer(PlayerMoveRequest::ChangeMap { index, x, y }, _)
+                        if *index == map_idx =>
+                    {
+                        shift(x, y)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Renames an event and everything that refers to it. Returns false if
+    /// there is no such event, it is locked, or the new tag is empty or
+    /// already used.
+    pub fn rename_event(&mut self, old: &str, new: &str) -> bool {
+        if new.is_empty()
+            || self.events.events.iter().any(|e| e.tag == new)
+            || self.is_event_locked(old)
+        {
+            return false;
+        }
+        match self.events.events.iter_mut().find(|e| e.tag == old) {
+            Some(event) => event.tag = new.to_string(),
+            None => return false,
+        }
+        for mut reference in self.event_references(old) {
+            reference.set(Some(new));
+        }
+        true
+    }
+
+    /// Deletes an event. Triggers, else branches and door events that ran
+    /// it are removed; anything else that named it is left empty. Returns
+    /// false if the event is locked.
+    pub fn delete_event(&mut self, tag: &str) -> bool {
+        if self.is_event_locked(tag) {
+            return false;
+        }
+        self.events.events.retain(|e| e.tag != tag);
+        for mut reference in self.event_references(tag) {
+            reference.set(None);
+        }
+        true
+    }
+
+    /// Events from `.pbs` scripts aren't saved back, so they can't be
+    /// renamed or deleted, and neither can the events they refer to.
+    pub fn is_event_locked(&self, tag: &str) -> bool {
+        self.events
+            .events
+            .iter()
+            .filter(|e| e.is_read_only())
+            .any(|e| e.tag == tag || e.steps.iter().any(|s| step_event_tags(s).contains(&tag)))
+    }
+
+    /// How many places run an event.
+    pub fn count_event_references(&self, tag: &str) -> usize {
+        let mut names = vec![self.module_start_event.as_str()];
+        for map in self.maps.values() {
+            names.push(&map.map_start_event);
+            for tile in map.tiles.iter() {
+                names.extend(tile.entry_trigger.as_deref());
+                names.extend(tile.exit_trigger.as_ref().map(|(_, t)| t.as_str()));
+            }
+            for door in map.doors.iter() {
+                names.extend(door.on_open.as_deref());
+            }
+        }
+        for event in self.events.events.iter().filter(|e| !e.is_read_only()) {
+            for step in event.steps.iter() {
+                names.extend(step_event_tags(step));
+            }
+        }
+        names.iter().filter(|n| **n == tag).count()
+    }
+
+    /// Everywhere `tag` is named: start events, tile triggers, door events,
+    /// calls and branches. Events from `.pbs` scripts are skipped.
+    fn event_references(&mut self, tag: &str) -> Vec<EventReference<'_>> {
+        let mut references = vec![EventReference::Text(&mut self.module_start_event)];
+        for map in self.maps.values_mut() {
+            references.push(EventReference::Text(&mut map.map_start_event));
+            for tile in map.tiles.iter_mut() {
+                references.push(EventReference::Optional(&mut tile.entry_trigger));
+                references.push(EventReference::Trigger(&mut tile.exit_trigger));
+            }
+            for door in map.doors.iter_mut() {
+                references.push(EventReference::Optional(&mut door.on_open));
+            }
+        }
+        for event in self.events.events.iter_mut() {
+            if event.is_read_only() {
+                continue;
+            }
+            for step in event.steps.iter_mut() {
+                step_references(step, &mut references);
+            }
+        }
+        references.retain(|r| r.names(tag));
+        references
+    }
+
     pub fn save(&self) {
         crate::modules::save_module(self).unwrap();
     }
 }
+
+/// Events run by a step: calls and branches.
+fn step_event_tags(step: &GameEventStep) -> Vec<&str> {
+    match step {
+        GameEventStep::CallEvent(called) => vec![called.as_str()],
+        GameEventStep::InputBranch { options, .. } => {
+            options.iter().map(|o| o.branch.as_str()).collect()
+        }
+        GameEventStep::IfFlag {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfNumber {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfText {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfHasItem {
+            then_branch,
+            else_branch,
+            ..
+        } => std::iter::once(then_branch.as_str())
+            .chain(else_branch.as_deref())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Event references made by a step: calls and branches.
+fn step_references<'a>(step: &'a mut GameEventStep, references: &mut Vec<EventReference<'a>>) {
+    match step {
+        GameEventStep::CallEvent(called) => references.push(EventReference::Text(called)),
+        GameEventStep::InputBranch { options, .. } => {
+            for option in options.iter_mut() {
+                references.push(EventReference::Text(&mut option.branch));
+            }
+        }
+        GameEventStep::IfFlag {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfNumber {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfText {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | GameEventStep::IfHasItem {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            references.push(EventReference::Text(then_branch));
+            references.push(EventReference::Optional(else_branch));
+        }
+        _ => {}
+    }
+}
+
+/// Somewhere in a module that names an event.
+enum EventReference<'a> {
+    /// Empty when there is no event
+    Text(&'a mut String),
+    Optional(&'a mut Option<String>),
+    Trigger(&'a mut Option<(Direction, String)>),
+}
+
+impl<'a> EventReference<'a> {
+    fn names(&self, tag: &str) -> bool {
+        match self {
+            EventReference::Text(t) => t.as_str() == tag,
+            EventReference::Optional(t) => t.as_deref() == Some(tag),
+            EventReference::Trigger(t) => matches!(&**t, Some((_, trigger)) if trigger == tag),
+        }
+    }
+
+    /// Points the reference at another event, or clears it.
+    fn set(&mut self, tag: Option<&str>) {
+        match self {
+            EventReference::Text(t) => **t = tag.unwrap_or_default().to_string(),
+            EventReference::Optional(t) => **t = tag.map(|t| t.to_string()),
+            EventReference::Trigger(t) => match tag {
+                Some(tag) => {
+                    if let Some((_, trigger)) = t {
+                        *trigger = tag.to_string();
+                    }
+                }
+                None => **t = None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::game_events::{GameEvent, InputChoice};
+
+    fn module() -> Module {
+        let mut module = Module::default();
+        module.module_start_event = "Intro".to_string();
+        let mut map = RegionMap::default();
+        map.tiles[0].entry_trigger = Some("Intro".to_string());
+        map.tiles[1].exit_trigger = Some((Direction::North, "Intro".to_string()));
+        module.maps.insert(0, map);
+        for (tag, steps) in [
+            ("Intro", Vec::new()),
+            (
+                "Menu",
+                vec![
+                    GameEventStep::CallEvent("Intro".to_string()),
+                    GameEventStep::InputBranch {
+                        title: String::new(),
+                        message: String::new(),
+                        portrait: None,
+                        options: vec![InputChoice {
+                            branch: "Intro".to_string(),
+                            message: "Again".to_string(),
+                        }],
+                    },
+                    GameEventStep::IfFlag {
+                        flag: "seen".to_string(),
+                        then_branch: "Menu".to_string(),
+                        else_branch: Some("Intro".to_string()),
+                    },
+                ],
+            ),
+        ] {
+            module.events.events.push(GameEvent {
+                tag: tag.to_string(),
+                steps,
+                filename: "scripts.ron".to_string(),
+            });
+        }
+        module
+    }
+
+    #[test]
+    fn rename_updates_references() {
+        let mut module = module();
+        assert_eq!(module.count_event_references("Intro"), 6);
+        assert!(module.rename_event("Intro", "Prologue"));
+        assert_eq!(module.count_event_references("Intro"), 0);
+        assert_eq!(module.count_event_references("Prologue"), 6);
+        assert_eq!(module.module_start_event, "Prologue");
+        assert!(module.events.events.iter().any(|e| e.tag == "Prologue"));
+    }
+
+    #[test]
+    fn rename_refuses_taken_tags() {
+        let mut module = module();
+        assert!(!module.rename_event("Intro", "Menu"));
+        assert!(!module.rename_event("Intro", ""));
+        assert!(!module.rename_event("Nothing", "Something"));
+        assert_eq!(module.module_start_event, "Intro");
+    }
+
+    #[test]
+    fn delete_clears_references() {
+        let mut module = module();
+        assert!(module.delete_event("Intro"));
+        assert_eq!(module.events.events.len(), 1);
+        assert!(module.module_start_event.is_empty());
+        let map = &module.maps[&0];
+        assert!(map.tiles[0].entry_trigger.is_none());
+        assert!(map.tiles[1].exit_trigger.is_none());
+        match &module.events.events[0].steps[2] {
+            GameEventStep::IfFlag {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                assert_eq!(then_branch, "Menu");
+                assert!(else_branch.is_none());
+            }
+            _ => panic!("Steps should not change"),
+        }
+    }
+
+    #[test]
+    fn script_file_events_are_locked() {
+        let mut module = module();
+        module.events.events.push(GameEvent {
+            tag: "Shop".to_string(),
+            steps: vec![GameEventStep::CallEvent("Menu".to_string())],
+            filename: "shop.pbs".to_string(),
+        });
+        assert!(!module.rename_event("Shop", "Store"));
+        assert!(!module.delete_event("Shop"));
+        // The script would still call the old tag after reloading
+        assert!(!module.rename_event("Menu", "Options"));
+        assert!(module.rename_event("Intro", "Prologue"));
+        assert_eq!(module.events.events.len(), 3);
+    }
+}
@@ -0,0 +1,60 @@
+use super::Dice;
+use serde::{Deserialize, Serialize};
+
+/// A kind of monster that encounters can place into battle.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MonsterTemplate {
+    pub tag: String,
+    pub name: String,
+    /// Key into `Module::sprites`. Monsters without a sprite are drawn as
+    /// colored squares.
+    pub sprite: Option<String>,
+    pub hit_dice: Dice,
+    pub armor_class: i32,
+    pub to_hit: i32,
+    pub initiative_bonus: i32,
+    pub movement: i32,
+    pub attacks: Vec<MonsterAttack>,
+    pub xp: i32,
+    pub gold: Dice,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MonsterAttack {
+    pub name: String,
+    pub damage: Dice,
+    /// Range in battle tiles. Melee attacks have a range of 1.
+    pub range: i32,
+}
+
+/// A named battle, referenced from `GameEventStep::Battle`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Encounter {
+    pub tag: String,
+    pub groups: Vec<EncounterGroup>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EncounterGroup {
+    /// Tag of the `MonsterTemplate` to spawn
+    pub monster: String,
+    pub count: Dice,
+    pub placement: Placement,
+}
+
+/// Where a group of monsters starts the battle, relative to the party.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Placement {
+    Far,
+    Near,
+    Random,
+}
+
+/// The contents of a file in a module's `monsters/` directory.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MonsterFile {
+    #[serde(default)]
+    pub monsters: Vec<MonsterTemplate>,
+    #[serde(default)]
+    pub encounters: Vec<Encounter>,
+}
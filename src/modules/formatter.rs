@@ -0,0 +1,160 @@
+use super::{material_loader::MaterialFile, ModuleHeader};
+use crate::{
+    module::{game_events::GameEvent, ItemFile, MonsterFile},
+    region::region_map::RegionMap,
+};
+use anyhow::{Error, Result};
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// What `format_module` did, or would do with `check` set.
+#[derive(Default)]
+pub struct FormatReport {
+    /// Files whose formatting changed
+    pub changed: Vec<PathBuf>,
+    /// Files left alone because they contain comments, which rewriting
+    /// would drop
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Rewrites every RON file in a module in the same layout `save_module`
+/// uses, which also writes out any fields left to their defaults. With
+/// `check` set, nothing is written and the changes are just reported.
+pub fn format_module(path: &Path, check: bool) -> Result<FormatReport> {
+    let mut report = FormatReport::default();
+    format_file::<ModuleHeader>(&path.join("header.ron"), check, &mut report)?;
+    format_dir::<RegionMap>(&path.join("maps"), check, &mut report)?;
+    format_dir::<MaterialFile>(&path.join("materials"), check, &mut report)?;
+    format_dir::<Vec<GameEvent>>(&path.join("scripts"), check, &mut report)?;
+    format_dir::<MonsterFile>(&path.join("monsters"), check, &mut report)?;
+    format_dir::<ItemFile>(&path.join("items"), check, &mut report)?;
+    Ok(report)
+}
+
+fn format_dir<T: DeserializeOwned + Serialize>(
+    path: &Path,
+    check: bool,
+    report: &mut FormatReport,
+) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(path)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().map_or(false, |ext| ext == "ron"))
+        .collect();
+    files.sort();
+    for file in files {
+        format_file::<T>(&file, check, report)?;
+    }
+    Ok(())
+}
+
+fn format_file<T: DeserializeOwned + Serialize>(
+    path: &Path,
+    check: bool,
+    report: &mut FormatReport,
+) -> Result<()> {
+    let data = fs::read_to_string(path)?;
+    if has_comments(&data) {
+        report.skipped.push(path.to_path_buf());
+        return Ok(());
+    }
+    let parsed: T =
+        ron::from_str(&data).map_err(|e| Error::msg(format!("{}: {}", path.display(), e)))?;
+    let formatted = to_string_pretty(&parsed, PrettyConfig::new())?;
+    if formatted != data {
+        if !check {
+            fs::write(path, formatted)?;
+        }
+        report.changed.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Does the RON contain a `//` or `/* */` comment outside a string?
+fn has_comments(data: &str) -> bool {
+    let mut chars = data.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '/' if !in_string => {
+                if matches!(chars.peek(), Some('/') | Some('*')) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary directory for one test, removed when dropped. Tests run
+    /// in parallel, so each gets its own.
+    struct ScratchDir(PathBuf);
+
+    impl ScratchDir {
+        fn new(test: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("pyrite-fmt-{}-{}", std::process::id(), test));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, name: &str, data: &str) -> PathBuf {
+            let path = self.0.join(name);
+            fs::write(&path, data).unwrap();
+            path
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn reformats_until_stable() {
+        let dir = ScratchDir::new("reformat");
+        let path = dir.file("plain.ron", "[(tag:\"Intro\",steps:[ClearLog])]");
+        let mut report = FormatReport::default();
+        format_file::<Vec<GameEvent>>(&path, true, &mut report).unwrap();
+        assert_eq!(report.changed, vec![path.clone()]);
+
+        format_file::<Vec<GameEvent>>(&path, false, &mut report).unwrap();
+        let mut again = FormatReport::default();
+        format_file::<Vec<GameEvent>>(&path, false, &mut again).unwrap();
+        assert!(again.changed.is_empty());
+    }
+
+    #[test]
+    fn files_with_comments_are_left_alone() {
+        let data = "// The opening\n[(tag:\"Intro\",steps:[ClearLog])]";
+        let dir = ScratchDir::new("comments");
+        let path = dir.file("commented.ron", data);
+        let mut report = FormatReport::default();
+        format_file::<Vec<GameEvent>>(&path, false, &mut report).unwrap();
+        assert_eq!(report.skipped, vec![path.clone()]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), data);
+    }
+
+    #[test]
+    fn slashes_in_strings_are_not_comments() {
+        assert!(!has_comments("(text: \"a // b\", path: \"x/*y\")"));
+        assert!(has_comments("(a: 1) /* note */"));
+    }
+}
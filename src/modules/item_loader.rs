@@ -0,0 +1,26 @@
+use crate::module::{ItemFile, ItemTemplate};
+use anyhow::Result;
+use std::{collections::HashMap, fs, path::Path};
+
+pub fn load_items(path: &Path) -> Result<HashMap<String, ItemTemplate>> {
+    let mut items = HashMap::new();
+
+    // Older modules don't have any items
+    if !path.exists() {
+        return Ok(items);
+    }
+
+    let paths = fs::read_dir(path)?;
+    for item_path in paths
+        .flatten()
+        .filter(|p| p.path().extension().map_or(false, |ext| ext == "ron"))
+    {
+        let data = std::fs::read_to_string(item_path.path())?;
+        let item_file: ItemFile = ron::from_str(&data)?;
+        for item in item_file.items {
+            items.insert(item.tag.clone(), item);
+        }
+    }
+
+    Ok(items)
+}
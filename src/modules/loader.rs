@@ -2,14 +2,29 @@ use super::ModuleHeader;
 use crate::{
     module::{game_events::EventList, Module},
     modules::{
-        map_loader::load_maps, material_loader::load_materials, scripts_loader::load_scripts,
+        item_loader::load_items, map_loader::load_maps, material_loader::load_materials,
+        monster_loader::load_monsters, scripts_loader::load_scripts, validate_module,
     },
 };
 use anyhow::{Error, Result};
 use std::path::Path;
 
+/// Loads a module, printing any validation problems found in it.
 pub fn load_module(path: &Path) -> Result<Module> {
     println!("{:?}", path);
+    let module = read_module(path)?;
+
+    // Broken references are reported rather than refused, so they can be
+    // fixed in the editor.
+    for issue in validate_module(&module) {
+        println!("{}", issue);
+    }
+
+    Ok(module)
+}
+
+/// Loads a module without validating it.
+pub fn read_module(path: &Path) -> Result<Module> {
     if !path.exists() {
         return Err(Error::msg("Module path not found"));
     }
@@ -41,8 +56,14 @@ pub fn load_module(path: &Path) -> Result<Module> {
     let mat_path = path.join("materials");
     let materials = load_materials(&mat_path)?;
 
-    let next_material_index = materials.keys().max().unwrap() + 1;
-    let next_map_index = maps.keys().max().unwrap() + 1;
+    // Monsters directory
+    let (monsters, encounters) = load_monsters(&path.join("monsters"))?;
+
+    // Items directory
+    let items = load_items(&path.join("items"))?;
+
+    let next_material_index = materials.keys().max().map_or(0, |m| m + 1);
+    let next_map_index = maps.keys().max().map_or(0, |m| m + 1);
 
     let module = Module {
         name: header.name,
@@ -58,6 +79,9 @@ pub fn load_module(path: &Path) -> Result<Module> {
         base_path,
         ui_images: header.ui_images,
         sprites: header.sprites,
+        monsters,
+        encounters,
+        items,
     };
 
     Ok(module)
@@ -1,9 +1,15 @@
 mod scanner;
 pub use scanner::{list_available_modules, ModuleHeader};
+mod formatter;
+mod item_loader;
+pub use formatter::{format_module, FormatReport};
 mod loader;
-pub use loader::load_module;
+pub use loader::{load_module, read_module};
 mod map_loader;
 mod material_loader;
+mod monster_loader;
 mod saver;
 mod scripts_loader;
 pub use saver::*;
+mod validator;
+pub use validator::*;
@@ -0,0 +1,32 @@
+use crate::module::{Encounter, MonsterFile, MonsterTemplate};
+use anyhow::Result;
+use std::{collections::HashMap, fs, path::Path};
+
+pub fn load_monsters(
+    path: &Path,
+) -> Result<(HashMap<String, MonsterTemplate>, HashMap<String, Encounter>)> {
+    let mut monsters = HashMap::new();
+    let mut encounters = HashMap::new();
+
+    // Older modules don't have any monsters
+    if !path.exists() {
+        return Ok((monsters, encounters));
+    }
+
+    let paths = fs::read_dir(path)?;
+    for monster_path in paths
+        .flatten()
+        .filter(|p| p.path().extension().map_or(false, |ext| ext == "ron"))
+    {
+        let data = std::fs::read_to_string(monster_path.path())?;
+        let monster_file: MonsterFile = ron::from_str(&data)?;
+        for monster in monster_file.monsters {
+            monsters.insert(monster.tag.clone(), monster);
+        }
+        for encounter in monster_file.encounters {
+            encounters.insert(encounter.tag.clone(), encounter);
+        }
+    }
+
+    Ok((monsters, encounters))
+}
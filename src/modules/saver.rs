@@ -1,8 +1,8 @@
 use super::{material_loader::MaterialFile, ModuleHeader};
-use crate::module::Module;
+use crate::module::{game_events::GameEvent, Module};
 use anyhow::{Error, Result};
 use ron::ser::{to_string_pretty, PrettyConfig};
-use std::{fs::create_dir, path::Path};
+use std::{collections::HashMap, fs::create_dir, path::Path};
 
 pub fn save_module(module: &Module) -> Result<()> {
     let base_path = Path::new(&module.base_path);
@@ -11,6 +11,8 @@ pub fn save_module(module: &Module) -> Result<()> {
         create_dir(base_path.join("maps"))?;
         create_dir(base_path.join("materials"))?;
         create_dir(base_path.join("scripts"))?;
+        create_dir(base_path.join("monsters"))?;
+        create_dir(base_path.join("items"))?;
     }
     if !base_path.is_dir() {
         return Err(Error::msg(
@@ -51,9 +53,38 @@ pub fn save_module(module: &Module) -> Result<()> {
         std::fs::write(Path::new(filename), mat_ron)?;
     }
 
-    // Save scripts
-    //let scripts_path = base_path.join("scripts");
-    // For now, scripts must be edited by hand
+    // Save scripts, putting each event back into the file it came from
+    let scripts_path = base_path.join("scripts");
+    if !scripts_path.exists() {
+        create_dir(&scripts_path)?;
+    }
+    let mut script_files: HashMap<String, Vec<&GameEvent>> = HashMap::new();
+    for event in module.events.events.iter() {
+        let filename = if event.filename.is_empty() {
+            module.events.filename.clone()
+        } else {
+            event.filename.clone()
+        };
+        script_files.entry(filename).or_default().push(event);
+    }
+    for (filename, events) in script_files.iter() {
+        // Scripts written by hand are the author's source of truth, so
+        // they are never rewritten as RON.
+        if events.iter().any(|e| e.is_read_only()) {
+            continue;
+        }
+        let script_ron = to_string_pretty(events, PrettyConfig::new())?;
+        std::fs::write(scripts_path.join(filename), script_ron)?;
+    }
+
+    // Remove script files whose events have all been deleted, otherwise
+    // they would come back on the next load.
+    for script_path in std::fs::read_dir(&scripts_path)?.flatten() {
+        let filename = script_path.file_name().to_str().unwrap().to_string();
+        if filename.ends_with(".ron") && !script_files.contains_key(&filename) {
+            std::fs::remove_file(script_path.path())?;
+        }
+    }
 
     Ok(())
 }
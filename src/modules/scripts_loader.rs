@@ -1,16 +1,31 @@
-use crate::module::game_events::GameEvent;
-use anyhow::Result;
+use crate::module::game_events::{parse_script, GameEvent, SCRIPT_EXTENSION};
+use anyhow::{Error, Result};
 use std::fs;
 use std::path::Path;
 
+/// Loads every script in `scripts/`: RON event lists, and `.pbs` files
+/// written in the script language.
 pub fn load_scripts(path: &Path) -> Result<Vec<GameEvent>> {
-    let paths = fs::read_dir(path).unwrap();
+    let paths = fs::read_dir(path)?;
 
     Ok(paths
         .flatten()
+        .filter(|p| {
+            p.path()
+                .extension()
+                .map_or(false, |ext| ext == "ron" || ext == SCRIPT_EXTENSION)
+        })
         .map(|script_path| {
             let data = std::fs::read_to_string(script_path.path())?;
-            let script = ron::from_str::<Vec<GameEvent>>(&data)?;
+            let filename = script_path.file_name().to_str().unwrap().to_string();
+            let mut script = if filename.ends_with(&format!(".{}", SCRIPT_EXTENSION)) {
+                parse_script(&data).map_err(|e| Error::msg(format!("{}:{}", filename, e)))?
+            } else {
+                ron::from_str::<Vec<GameEvent>>(&data)?
+            };
+            script
+                .iter_mut()
+                .for_each(|event| event.filename = filename.clone());
             Ok(script)
         })
         .collect::<Result<Vec<Vec<_>>>>()?
@@ -0,0 +1,448 @@
+use crate::{
+    game_states::{player_movement::PlayerMoveRequest, sprites::SpriteRequest},
+    module::{
+        game_events::{GameEvent, GameEventStep},
+        Dice, ItemKind, Module,
+    },
+    region::region_map::{RegionBoundaryType, RegionMap, RegionTileType},
+};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    /// Will crash or break the game when reached
+    Error,
+    /// Probably a mistake, but the game copes
+    Warning,
+}
+
+/// A problem found in a module, and where it was found.
+#[derive(Clone, Debug)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub location: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}: {}: {}",
+            self.severity, self.location, self.message
+        )
+    }
+}
+
+struct Validator<'a> {
+    module: &'a Module,
+    issues: Vec<ValidationIssue>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, location: &str, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn warning(&mut self, location: &str, message: String) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn has_event(&self, tag: &str) -> bool {
+        self.module.events.events.iter().any(|e| e.tag == tag)
+    }
+
+    /// Empty tags mean "no event", so only named events are checked.
+    fn check_event(&mut self, location: &str, tag: &str) {
+        if !tag.is_empty() && !self.has_event(tag) {
+            self.error(location, format!("Event '{}' does not exist", tag));
+        }
+    }
+
+    fn check_material(&mut self, location: &str, material: u32) {
+        if !self.module.materials.contains_key(&(material as usize)) {
+            self.error(location, format!("Material {} does not exist", material));
+        }
+    }
+
+    fn check_item(&mut self, location: &str, tag: &str) {
+        if !self.module.items.contains_key(tag) {
+            self.warning(location, format!("Item '{}' is not defined", tag));
+        }
+    }
+
+    fn check_dice(&mut self, location: &str, what: &str, dice: Dice) {
+        if !dice.is_valid() {
+            self.error(location, format!("{} {} can't be rolled", what, dice));
+        }
+    }
+
+    fn check_module(&mut self) {
+        let module = self.module;
+        if module.maps.is_empty() {
+            self.error("Module", "The module has no maps".to_string());
+        } else if !module.maps.contains_key(&module.starting_map_idx) {
+            self.error(
+                "Module",
+                format!("Starting map {} does not exist", module.starting_map_idx),
+            );
+        }
+        self.check_event("Module start event", &module.module_start_event);
+    }
+
+    fn check_map(&mut self, idx: usize, map: &RegionMap) {
+        let location = format!("Map {} ({})", idx, map.name);
+        if map.index != idx {
+            self.warning(
+                &location,
+                format!("Map index {} does not match its key {}", map.index, idx),
+            );
+        }
+        self.check_event(&format!("{} start event", location), &map.map_start_event);
+
+        let expected = map.size.0 as usize * map.size.1 as usize;
+        if map.tiles.len() != expected {
+            // Tile coordinates are meaningless from here on
+            self.error(
+                &location,
+                format!(
+                    "Map is {}x{} but has {} tiles (expected {})",
+                    map.size.0,
+                    map.size.1,
+                    map.tiles.len(),
+                    expected
+                ),
+            );
+            return;
+        }
+
+        let (sx, sy, _) = map.starting_location;
+        if !map.in_bounds(sx as i32, sy as i32) {
+            self.error(
+                &location,
+                format!("Starting location {},{} is off the map", sx, sy),
+            );
+        }
+
+        for y in 0..map.size.1 as i32 {
+            for x in 0..map.size.0 as i32 {
+                let tile = &map.tiles[map.tile_idx(x, y)];
+                let tile_location = format!("{} tile {},{}", location, x, y);
+                if tile.tile_type != RegionTileType::Empty {
+                    self.check_material(&tile_location, tile.floor_material);
+                }
+                if tile.has_ceiling {
+                    self.check_material(&tile_location, tile.ceiling_material);
+                }
+                for (boundary, material) in tile.boundaries.iter() {
+                    if *boundary != RegionBoundaryType::None {
+                        self.check_material(&tile_location, *material);
+                    }
+                }
+                if let Some(tag) = &tile.entry_trigger {
+                    self.check_event(&tile_location, tag);
+                }
+                if let Some((_, tag)) = &tile.exit_trigger {
+                    self.check_event(&tile_location, tag);
+                }
+            }
+        }
+
+        for door in map.doors.iter() {
+            let door_location = format!(
+                "{} door {},{} {:?}",
+                location, door.x, door.y, door.direction
+            );
+            if !map.is_door(door.x as i32, door.y as i32, door.direction) {
+                self.warning(
+                    &door_location,
+                    "Door state is stored for an edge without a door".to_string(),
+                );
+            }
+            if let Some(key) = &door.key {
+                self.check_item(&door_location, key);
+            }
+            if let Some(tag) = &door.on_open {
+                self.check_event(&door_location, tag);
+            }
+        }
+    }
+
+    fn check_map_position(&mut self, location: &str, index: usize, x: u32, y: u32) {
+        let module = self.module;
+        match module.maps.get(&index) {
+            None => self.error(location, format!("Map {} does not exist", index)),
+            Some(map) if !map.in_bounds(x as i32, y as i32) => self.error(
+                location,
+                format!("{},{} is outside map {} ({})", x, y, index, map.name),
+            ),
+            _ => {}
+        }
+    }
+
+    fn check_event_steps(&mut self, event: &GameEvent) {
+        for (line, step) in event.steps.iter().enumerate() {
+            let location = format!("Event {} step {}", event.tag, line + 1);
+            match step {
+                GameEventStep::CallEvent(tag) => self.check_event(&location, tag),
+                GameEventStep::ChangeMap { index, x, y }
+                | GameEventStep::MovePlayer(PlayerMoveRequest::ChangeMap { index, x, y }, _) => {
+                    self.check_map_position(&location, *index, *x, *y)
+                }
+                GameEventStep::InputBranch {
+                    portrait, options, ..
+                } => {
+                    if options.is_empty() {
+                        self.error(
+                            &location,
+                            "Input has no options, so the script can never continue".to_string(),
+                        );
+                    }
+                    for option in options.iter() {
+                        self.check_event(&location, &option.branch);
+                    }
+                    if let Some(portrait) = portrait {
+                        if !self.module.ui_images.iter().any(|(k, _)| k == portrait) {
+                            self.warning(
+                                &location,
+                                format!("UI image '{}' does not exist", portrait),
+                            );
+                        }
+                    }
+                }
+                GameEventStep::Sprite(SpriteRequest::Spawn { image, .. }) => {
+                    if !self.module.sprites.iter().any(|(k, _)| k == image) {
+                        self.error(&location, format!("Sprite '{}' does not exist", image));
+                    }
+                }
+                GameEventStep::Sprite(SpriteRequest::Walk { step_ms: 0, .. }) => {
+                    self.error(&location, "Sprites can't walk a step every 0ms".to_string());
+                }
+                GameEventStep::Battle(encounter) => {
+                    if !self.module.encounters.contains_key(encounter) {
+                        self.error(
+                            &location,
+                            format!("Encounter '{}' does not exist", encounter),
+                        );
+                    }
+                }
+                GameEventStep::IfFlag {
+                    then_branch,
+                    else_branch,
+                    ..
+                }
+                | GameEventStep::IfNumber {
+                    then_branch,
+                    else_branch,
+                    ..
+                }
+                | GameEventStep::IfText {
+                    then_branch,
+                    else_branch,
+                    ..
+                } => {
+                    self.check_event(&location, then_branch);
+                    if let Some(tag) = else_branch {
+                        self.check_event(&location, tag);
+                    }
+                }
+                GameEventStep::GiveItem(item) | GameEventStep::TakeItem(item) => {
+                    self.check_item(&location, item)
+                }
+                GameEventStep::IfHasItem {
+                    item,
+                    then_branch,
+                    else_branch,
+                } => {
+                    self.check_item(&location, item);
+                    self.check_event(&location, then_branch);
+                    if let Some(tag) = else_branch {
+                        self.check_event(&location, tag);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_events(&mut self) {
+        let module = self.module;
+        let mut seen = HashSet::new();
+        for event in module.events.events.iter() {
+            if !seen.insert(&event.tag) {
+                self.error(
+                    &format!("Event {}", event.tag),
+                    "Another event has the same tag".to_string(),
+                );
+            }
+            self.check_event_steps(event);
+        }
+    }
+
+    fn check_monsters(&mut self) {
+        let module = self.module;
+        for monster in module.monsters.values() {
+            let location = format!("Monster {}", monster.tag);
+            self.check_dice(&location, "Hit dice", monster.hit_dice);
+            self.check_dice(&location, "Gold", monster.gold);
+            for attack in monster.attacks.iter() {
+                self.check_dice(&location, &format!("{} damage", attack.name), attack.damage);
+            }
+            if let Some(sprite) = &monster.sprite {
+                if !module.sprites.iter().any(|(k, _)| k == sprite) {
+                    self.warning(&location, format!("Sprite '{}' does not exist", sprite));
+                }
+            }
+        }
+        for encounter in module.encounters.values() {
+            for group in encounter.groups.iter() {
+                self.check_dice(
+                    &format!("Encounter {}", encounter.tag),
+                    &format!("{} count", group.monster),
+                    group.count,
+                );
+                if !module.monsters.contains_key(&group.monster) {
+                    self.error(
+                        &format!("Encounter {}", encounter.tag),
+                        format!("Monster '{}' does not exist", group.monster),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_items(&mut self) {
+        let module = self.module;
+        for item in module.items.values() {
+            let location = format!("Item {}", item.tag);
+            match &item.kind {
+                ItemKind::Weapon { damage, .. } => self.check_dice(&location, "Damage", *damage),
+                ItemKind::Consumable { heal } => self.check_dice(&location, "Healing", *heal),
+                ItemKind::Armor { .. } | ItemKind::Quest => {}
+            }
+        }
+    }
+}
+
+/// Checks a module for broken references and malformed maps. Errors are
+/// things that will crash or stall the game; warnings are likely mistakes.
+pub fn validate_module(module: &Module) -> Vec<ValidationIssue> {
+    let mut validator = Validator {
+        module,
+        issues: Vec::new(),
+    };
+    validator.check_module();
+
+    let mut map_indices: Vec<&usize> = module.maps.keys().collect();
+    map_indices.sort();
+    for idx in map_indices {
+        validator.check_map(*idx, &module.maps[idx]);
+    }
+
+    validator.check_events();
+    validator.check_monsters();
+    validator.check_items();
+    validator.issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_with_map() -> Module {
+        let mut module = Module::default();
+        module.maps.insert(0, RegionMap::default());
+        module
+    }
+
+    fn errors(module: &Module) -> Vec<ValidationIssue> {
+        validate_module(module)
+            .into_iter()
+            .filter(|i| i.severity == Severity::Error)
+            .collect()
+    }
+
+    #[test]
+    fn default_module_with_a_map_is_valid() {
+        assert!(validate_module(&module_with_map()).is_empty());
+    }
+
+    #[test]
+    fn missing_material() {
+        let mut module = module_with_map();
+        module.maps.get_mut(&0).unwrap().tiles[3].floor_material = 42;
+        let errors = errors(&module);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].location.contains("tile 3,0"));
+    }
+
+    #[test]
+    fn wrong_tile_count() {
+        let mut module = module_with_map();
+        module.maps.get_mut(&0).unwrap().tiles.pop();
+        assert_eq!(errors(&module).len(), 1);
+    }
+
+    #[test]
+    fn missing_trigger_and_map() {
+        let mut module = module_with_map();
+        module.maps.get_mut(&0).unwrap().tiles[0].entry_trigger = Some("Nope".to_string());
+        module.events.events.push(GameEvent {
+            tag: "Travel".to_string(),
+            steps: vec![GameEventStep::ChangeMap {
+                index: 7,
+                x: 0,
+                y: 0,
+            }],
+            filename: "scripts.ron".to_string(),
+        });
+        let errors = errors(&module);
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|e| e.message.contains("'Nope'")));
+        assert!(errors.iter().any(|e| e.message.contains("Map 7")));
+    }
+
+    #[test]
+    fn unrollable_dice() {
+        let mut module = module_with_map();
+        module.items.insert(
+            "Potion".to_string(),
+            crate::module::ItemTemplate {
+                tag: "Potion".to_string(),
+                name: "Potion".to_string(),
+                description: String::new(),
+                kind: ItemKind::Consumable {
+                    heal: Dice::new(1, 0, 2),
+                },
+            },
+        );
+        let errors = errors(&module);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location, "Item Potion");
+    }
+
+    #[test]
+    fn walking_needs_a_step_time() {
+        let mut module = module_with_map();
+        module.events.events.push(GameEvent {
+            tag: "Patrol".to_string(),
+            steps: vec![GameEventStep::Sprite(SpriteRequest::Walk {
+                id: "guard".to_string(),
+                destination: (1, 1),
+                step_ms: 0,
+            })],
+            filename: "scripts.ron".to_string(),
+        });
+        let errors = errors(&module);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("0ms"));
+    }
+}
@@ -0,0 +1,125 @@
+use super::{RegionBoundaryType, RegionMap};
+use crate::module::Direction;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DoorState {
+    Open,
+    Closed,
+    Locked,
+}
+
+impl DoorState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DoorState::Open => "Open",
+            DoorState::Closed => "Closed",
+            DoorState::Locked => "Locked",
+        }
+    }
+}
+
+/// The state of a door boundary. Both sides of an edge share one `Door`,
+/// stored against the tile to the south or east of the edge (see
+/// `RegionMap::door_edge`).
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Door {
+    pub x: u32,
+    pub y: u32,
+    pub direction: Direction,
+    pub state: DoorState,
+    /// Item tag that unlocks the door
+    pub key: Option<String>,
+    /// Event to run when the door is opened by the player
+    pub on_open: Option<String>,
+}
+
+impl RegionMap {
+    /// Doors are stored against the north or west edge of a tile, so both
+    /// sides of a wall find the same entry.
+    pub fn door_edge(&self, x: i32, y: i32, direction: Direction) -> (u32, u32, Direction) {
+        let (dx, dy) = direction.delta_forward();
+        match direction {
+            Direction::South | Direction::East if self.in_bounds(x + dx, y + dy) => {
+                ((x + dx) as u32, (y + dy) as u32, direction.opposite())
+            }
+            _ => (x as u32, y as u32, direction),
+        }
+    }
+
+    /// Is there a door on either side of this edge?
+    pub fn is_door(&self, x: i32, y: i32, direction: Direction) -> bool {
+        let (dx, dy) = direction.delta_forward();
+        let is_door = |x: i32, y: i32, direction: Direction| {
+            self.in_bounds(x, y)
+                && self.tiles[self.tile_idx(x, y)].boundaries[direction.to_exit_index()].0
+                    == RegionBoundaryType::Door
+        };
+        is_door(x, y, direction) || is_door(x + dx, y + dy, direction.opposite())
+    }
+
+    pub fn door(&self, x: i32, y: i32, direction: Direction) -> Option<&Door> {
+        let (ex, ey, edir) = self.door_edge(x, y, direction);
+        self.doors
+            .iter()
+            .find(|d| d.x == ex && d.y == ey && d.direction == edir)
+    }
+
+    /// The door on this edge, creating a closed one if the edge doesn't
+    /// have any door state yet.
+    pub fn door_mut(&mut self, x: i32, y: i32, direction: Direction) -> &mut Door {
+        let (ex, ey, edir) = self.door_edge(x, y, direction);
+        let idx = match self
+            .doors
+            .iter()
+            .position(|d| d.x == ex && d.y == ey && d.direction == edir)
+        {
+            Some(idx) => idx,
+            None => {
+                self.doors.push(Door {
+                    x: ex,
+                    y: ey,
+                    direction: edir,
+                    state: DoorState::Closed,
+                    key: None,
+                    on_open: None,
+                });
+                self.doors.len() - 1
+            }
+        };
+        &mut self.doors[idx]
+    }
+
+    /// Doors without state are closed.
+    pub fn door_state(&self, x: i32, y: i32, direction: Direction) -> DoorState {
+        self.door(x, y, direction)
+            .map_or(DoorState::Closed, |d| d.state)
+    }
+
+    pub fn set_door_state(&mut self, x: i32, y: i32, direction: Direction, state: DoorState) {
+        self.door_mut(x, y, direction).state = state;
+        self.needs_rebuild = true;
+    }
+
+    /// Forget the state of doors that have been removed from the map.
+    pub fn prune_doors(&mut self) {
+        let doors = std::mem::take(&mut self.doors);
+        self.doors = doors
+            .into_iter()
+            .filter(|d| self.is_door(d.x as i32, d.y as i32, d.direction))
+            .collect();
+    }
+
+    /// The boundary as movement sees it: open doors behave like openings,
+    /// closed and locked ones like walls.
+    pub fn effective_boundary(&self, x: i32, y: i32, direction: Direction) -> RegionBoundaryType {
+        let boundary = self.tiles[self.tile_idx(x, y)].boundaries[direction.to_exit_index()].0;
+        match boundary {
+            RegionBoundaryType::Door => match self.door_state(x, y, direction) {
+                DoorState::Open => RegionBoundaryType::Opening,
+                _ => RegionBoundaryType::Wall,
+            },
+            _ => boundary,
+        }
+    }
+}
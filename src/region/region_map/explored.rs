@@ -0,0 +1,121 @@
+use super::{RegionBoundaryType, RegionMap, SightGrid};
+use crate::module::Direction;
+use serde::{Deserialize, Serialize};
+
+/// How many tiles away the party can make things out.
+const VIEW_DISTANCE: i32 = 3;
+
+/// Set once a tile's floor has been seen. The low four bits record which
+/// of its walls have been seen, by exit index.
+const TILE_SEEN: u8 = 1 << 4;
+
+/// The parts of a map the party has seen, for the automap.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ExploredMap {
+    seen: Vec<u8>,
+}
+
+impl ExploredMap {
+    pub fn new(map: &RegionMap) -> Self {
+        Self {
+            seen: vec![0; map.tiles.len()],
+        }
+    }
+
+    /// Marks what is visible from x/y facing `facing`: every tile in
+    /// sight that isn't behind the party, and the far side of anything
+    /// blocking the view from those tiles.
+    pub fn reveal(&mut self, map: &RegionMap, x: i32, y: i32, facing: Direction) {
+        if self.seen.len() != map.tiles.len() {
+            // The map was resized since we last saw it
+            *self = Self::new(map);
+        }
+        if !map.in_bounds(x, y) {
+            return;
+        }
+
+        let (fx, fy) = facing.delta_forward();
+        for (tx, ty) in map.visible_tiles(x, y, VIEW_DISTANCE) {
+            if (tx - x) * fx + (ty - y) * fy < 0 {
+                continue;
+            }
+            self.see_tile(map, tx, ty);
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.delta_forward();
+                let own_wall = map.tiles[map.tile_idx(tx, ty)].boundaries
+                    [direction.to_exit_index()]
+                .0 == RegionBoundaryType::Wall;
+                if !own_wall
+                    && map.blocks_sight(tx, ty, direction)
+                    && map.in_bounds(tx + dx, ty + dy)
+                {
+                    self.see_wall(map, tx + dx, ty + dy, direction.opposite());
+                }
+            }
+        }
+    }
+
+    pub fn tile_seen(&self, idx: usize) -> bool {
+        self.seen.get(idx).map_or(false, |s| s & TILE_SEEN != 0)
+    }
+
+    pub fn wall_seen(&self, idx: usize, direction: Direction) -> bool {
+        self.seen
+            .get(idx)
+            .map_or(false, |s| s & (1 << direction.to_exit_index()) != 0)
+    }
+
+    fn see_tile(&mut self, map: &RegionMap, x: i32, y: i32) {
+        self.seen[map.tile_idx(x, y)] = TILE_SEEN | 0b1111;
+    }
+
+    fn see_wall(&mut self, map: &RegionMap, x: i32, y: i32, direction: Direction) {
+        self.seen[map.tile_idx(x, y)] |= 1 << direction.to_exit_index();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::region_map::RegionBoundaryType;
+
+    #[test]
+    fn sees_down_a_corridor() {
+        let map = RegionMap::new(1, 8);
+        let mut explored = ExploredMap::new(&map);
+        explored.reveal(&map, 0, 5, Direction::North);
+
+        for y in 2..6 {
+            assert!(explored.tile_seen(map.tile_idx(0, y)));
+        }
+        // Too far away, and behind the party
+        assert!(!explored.tile_seen(map.tile_idx(0, 1)));
+        assert!(!explored.tile_seen(map.tile_idx(0, 6)));
+    }
+
+    #[test]
+    fn walls_block_the_view() {
+        let mut map = RegionMap::new(3, 3);
+        let idx = map.tile_idx(1, 1);
+        map.tiles[idx].boundaries[Direction::North.to_exit_index()].0 = RegionBoundaryType::Wall;
+        let mut explored = ExploredMap::new(&map);
+        explored.reveal(&map, 1, 2, Direction::North);
+
+        assert!(explored.tile_seen(map.tile_idx(1, 1)));
+        assert!(explored.tile_seen(map.tile_idx(0, 1)));
+        assert!(!explored.tile_seen(map.tile_idx(1, 0)));
+    }
+
+    #[test]
+    fn sees_the_far_side_of_one_sided_walls() {
+        let mut map = RegionMap::new(1, 3);
+        let idx = map.tile_idx(0, 0);
+        map.tiles[idx].boundaries[Direction::South.to_exit_index()].0 = RegionBoundaryType::Wall;
+        let mut explored = ExploredMap::new(&map);
+        explored.reveal(&map, 0, 2, Direction::North);
+
+        assert!(!explored.tile_seen(idx));
+        assert!(explored.wall_seen(idx, Direction::South));
+        assert!(!explored.wall_seen(idx, Direction::North));
+    }
+}
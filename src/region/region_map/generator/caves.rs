@@ -0,0 +1,93 @@
+use super::{Cell, GeneratorSettings, Grid};
+use bracket_random::prelude::RandomNumberGenerator;
+
+/// Random noise smoothed into caves with a cellular automaton. Only the
+/// largest cave is kept, so everything is reachable. Returns the grid and
+/// the open tile closest to the middle of the map.
+pub(super) fn build(
+    size: (u32, u32),
+    settings: &GeneratorSettings,
+    rng: &mut RandomNumberGenerator,
+) -> (Grid, (u32, u32)) {
+    let mut grid = Grid::new(size, Cell::Rock);
+    for cell in grid.cells.iter_mut() {
+        if rng.range(0, 100) >= settings.fill_percent as i32 {
+            *cell = Cell::Room;
+        }
+    }
+
+    for _ in 0..settings.iterations {
+        let mut next = grid.cells.clone();
+        for y in 0..size.1 as i32 {
+            for x in 0..size.0 as i32 {
+                let mut rock = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx != 0 || dy != 0) && grid.get(x + dx, y + dy) == Cell::Rock {
+                            rock += 1;
+                        }
+                    }
+                }
+                let idx = grid.idx(x, y);
+                if rock > 4 {
+                    next[idx] = Cell::Rock;
+                } else if rock < 4 {
+                    next[idx] = Cell::Room;
+                }
+            }
+        }
+        grid.cells = next;
+    }
+
+    keep_largest_cave(&mut grid);
+
+    let middle = (size.0 as i32 / 2, size.1 as i32 / 2);
+    let start = (0..size.1 as i32)
+        .flat_map(|y| (0..size.0 as i32).map(move |x| (x, y)))
+        .filter(|(x, y)| grid.get(*x, *y) == Cell::Room)
+        .min_by_key(|(x, y)| (x - middle.0).pow(2) + (y - middle.1).pow(2))
+        .unwrap_or(middle);
+    grid.set(start.0, start.1, Cell::Room);
+    (grid, (start.0 as u32, start.1 as u32))
+}
+
+/// Fills in every cave except the biggest one.
+fn keep_largest_cave(grid: &mut Grid) {
+    let mut cave_of = vec![None; grid.cells.len()];
+    let mut sizes = Vec::new();
+    for (start, cell) in grid.cells.iter().enumerate() {
+        if *cell == Cell::Rock || cave_of[start].is_some() {
+            continue;
+        }
+        let cave = sizes.len();
+        let mut size = 0;
+        let mut open = vec![start];
+        cave_of[start] = Some(cave);
+        while let Some(idx) = open.pop() {
+            size += 1;
+            let (x, y) = (
+                (idx % grid.size.0 as usize) as i32,
+                (idx / grid.size.0 as usize) as i32,
+            );
+            for (dx, dy) in [(0, -1), (0, 1), (1, 0), (-1, 0)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if grid.get(nx, ny) == Cell::Rock {
+                    continue;
+                }
+                let next = grid.idx(nx, ny);
+                if cave_of[next].is_none() {
+                    cave_of[next] = Some(cave);
+                    open.push(next);
+                }
+            }
+        }
+        sizes.push(size);
+    }
+
+    let largest = (0..sizes.len()).max_by_key(|cave| sizes[*cave]);
+    for (cell, cave) in grid.cells.iter_mut().zip(cave_of) {
+        if cave != largest {
+            *cell = Cell::Rock;
+        }
+    }
+}
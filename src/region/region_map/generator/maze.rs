@@ -0,0 +1,57 @@
+use super::{blank_tile, GeneratorSettings};
+use crate::{
+    module::Direction,
+    region::region_map::{RegionBoundaryType, RegionMap, RegionTileType},
+};
+use bracket_random::prelude::RandomNumberGenerator;
+
+/// A perfect maze with thin walls between tiles, dug with a randomized
+/// depth-first search. Returns the map and a random starting tile.
+pub(super) fn build(
+    size: (u32, u32),
+    settings: &GeneratorSettings,
+    rng: &mut RandomNumberGenerator,
+) -> (RegionMap, (u32, u32)) {
+    let mut map = RegionMap::new(size.0, size.1);
+    for tile in map.tiles.iter_mut() {
+        *tile = blank_tile(settings);
+        tile.tile_type = RegionTileType::Floor;
+        tile.has_ceiling = settings.ceiling_material.is_some();
+        for boundary in tile.boundaries.iter_mut() {
+            boundary.0 = RegionBoundaryType::Wall;
+        }
+    }
+
+    let start = (rng.range(0, size.0 as i32), rng.range(0, size.1 as i32));
+    let mut visited = vec![false; map.tiles.len()];
+    visited[map.tile_idx(start.0, start.1)] = true;
+    let mut stack = vec![start];
+    while let Some(&(x, y)) = stack.last() {
+        let options: Vec<Direction> = Direction::ALL
+            .iter()
+            .copied()
+            .filter(|d| {
+                let (dx, dy) = d.delta_forward();
+                map.in_bounds(x + dx, y + dy) && !visited[map.tile_idx(x + dx, y + dy)]
+            })
+            .collect();
+
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let direction = options[rng.range(0, options.len())];
+        let (dx, dy) = direction.delta_forward();
+        let (nx, ny) = (x + dx, y + dy);
+
+        let here = map.tile_idx(x, y);
+        let there = map.tile_idx(nx, ny);
+        map.tiles[here].boundaries[direction.to_exit_index()].0 = RegionBoundaryType::None;
+        map.tiles[there].boundaries[direction.opposite().to_exit_index()].0 =
+            RegionBoundaryType::None;
+        visited[there] = true;
+        stack.push((nx, ny));
+    }
+
+    (map, (start.0 as u32, start.1 as u32))
+}
@@ -0,0 +1,266 @@
+use super::{RegionBoundaryType, RegionMap, RegionTile, RegionTileType};
+use crate::module::Direction;
+use bracket_random::prelude::RandomNumberGenerator;
+use std::collections::HashSet;
+mod caves;
+mod maze;
+mod rooms;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorAlgorithm {
+    Rooms,
+    Caves,
+    Maze,
+}
+
+impl GeneratorAlgorithm {
+    pub const ALL: [GeneratorAlgorithm; 3] = [
+        GeneratorAlgorithm::Rooms,
+        GeneratorAlgorithm::Caves,
+        GeneratorAlgorithm::Maze,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorAlgorithm::Rooms => "Rooms and Corridors",
+            GeneratorAlgorithm::Caves => "Caves",
+            GeneratorAlgorithm::Maze => "Maze",
+        }
+    }
+}
+
+/// Everything needed to generate a map. The same settings always give the
+/// same map.
+#[derive(Clone, PartialEq)]
+pub struct GeneratorSettings {
+    pub algorithm: GeneratorAlgorithm,
+    pub seed: u64,
+    pub size: (u32, u32),
+    pub floor_material: u32,
+    pub wall_material: u32,
+    pub ceiling_material: Option<u32>,
+    /// Rooms: how many rooms to try to place
+    pub max_rooms: u32,
+    /// Rooms: smallest and largest room width/height
+    pub room_size: (u32, u32),
+    /// Caves: percentage of tiles that start out as rock
+    pub fill_percent: u32,
+    /// Caves: smoothing passes
+    pub iterations: u32,
+}
+
+impl GeneratorSettings {
+    pub fn default() -> Self {
+        Self {
+            algorithm: GeneratorAlgorithm::Rooms,
+            seed: 1,
+            size: (32, 32),
+            floor_material: 0,
+            wall_material: 1,
+            ceiling_material: None,
+            max_rooms: 12,
+            room_size: (3, 7),
+            fill_percent: 45,
+            iterations: 4,
+        }
+    }
+}
+
+/// Builds a map from the settings. Every floor tile can be reached from
+/// the starting location.
+pub fn generate_map(settings: &GeneratorSettings) -> RegionMap {
+    let size = (u32::max(settings.size.0, 2), u32::max(settings.size.1, 2));
+    let mut rng = RandomNumberGenerator::seeded(settings.seed);
+    let (mut map, start) = match settings.algorithm {
+        GeneratorAlgorithm::Rooms => {
+            let (grid, start) = rooms::build(size, settings, &mut rng);
+            (grid.to_map(settings), start)
+        }
+        GeneratorAlgorithm::Caves => {
+            let (grid, start) = caves::build(size, settings, &mut rng);
+            (grid.to_map(settings), start)
+        }
+        GeneratorAlgorithm::Maze => maze::build(size, settings, &mut rng),
+    };
+
+    let facing = Direction::ALL
+        .iter()
+        .copied()
+        .find(|d| map.can_move(start.0 as i32, start.1 as i32, *d))
+        .unwrap_or(Direction::North);
+    map.starting_location = (start.0, start.1, facing);
+    map.name = format!("{} {}", settings.algorithm.name(), settings.seed);
+    map
+}
+
+/// What a generated tile will become.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Rock,
+    Room,
+    Corridor,
+}
+
+/// Rooms and caves are carved out of a grid of cells, then walled in.
+struct Grid {
+    size: (u32, u32),
+    cells: Vec<Cell>,
+    /// Pairs of cell indices (lowest first) where a corridor enters a room,
+    /// which become openings.
+    doorways: HashSet<(usize, usize)>,
+}
+
+impl Grid {
+    fn new(size: (u32, u32), fill: Cell) -> Self {
+        Self {
+            size,
+            cells: vec![fill; (size.0 * size.1) as usize],
+            doorways: HashSet::new(),
+        }
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.size.0 as i32 && y < self.size.1 as i32
+    }
+
+    fn idx(&self, x: i32, y: i32) -> usize {
+        (y * self.size.0 as i32 + x) as usize
+    }
+
+    /// Anything off the grid counts as rock.
+    fn get(&self, x: i32, y: i32) -> Cell {
+        if self.in_bounds(x, y) {
+            self.cells[self.idx(x, y)]
+        } else {
+            Cell::Rock
+        }
+    }
+
+    fn set(&mut self, x: i32, y: i32, cell: Cell) {
+        let idx = self.idx(x, y);
+        self.cells[idx] = cell;
+    }
+
+    fn add_doorway(&mut self, a: (i32, i32), b: (i32, i32)) {
+        let (a, b) = (self.idx(a.0, a.1), self.idx(b.0, b.1));
+        self.doorways.insert((usize::min(a, b), usize::max(a, b)));
+    }
+
+    fn to_map(&self, settings: &GeneratorSettings) -> RegionMap {
+        let mut map = RegionMap::new(self.size.0, self.size.1);
+        for y in 0..self.size.1 as i32 {
+            for x in 0..self.size.0 as i32 {
+                let idx = self.idx(x, y);
+                let cell = self.cells[idx];
+                let mut tile = blank_tile(settings);
+                if cell != Cell::Rock {
+                    tile.tile_type = RegionTileType::Floor;
+                    tile.has_ceiling = settings.ceiling_material.is_some();
+                    for (i, boundary) in tile.boundaries.iter_mut().enumerate() {
+                        let (dx, dy) = Direction::from(i).delta_forward();
+                        let neighbour = self.get(x + dx, y + dy);
+                        boundary.0 = if neighbour == Cell::Rock {
+                            RegionBoundaryType::Wall
+                        } else if neighbour == cell {
+                            RegionBoundaryType::None
+                        } else {
+                            let other = self.idx(x + dx, y + dy);
+                            if self
+                                .doorways
+                                .contains(&(usize::min(idx, other), usize::max(idx, other)))
+                            {
+                                RegionBoundaryType::Opening
+                            } else {
+                                RegionBoundaryType::Wall
+                            }
+                        };
+                    }
+                }
+                map.tiles[idx] = tile;
+            }
+        }
+        map
+    }
+}
+
+/// An empty tile using the generator's materials.
+fn blank_tile(settings: &GeneratorSettings) -> RegionTile {
+    RegionTile {
+        tile_type: RegionTileType::Empty,
+        has_ceiling: false,
+        boundaries: [(RegionBoundaryType::None, settings.wall_material); 4],
+        floor_material: settings.floor_material,
+        ceiling_material: settings.ceiling_material.unwrap_or(settings.floor_material),
+        entry_trigger: None,
+        exit_trigger: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every floor tile can be walked to from the start.
+    fn assert_connected(map: &RegionMap) {
+        let (sx, sy, _) = map.starting_location;
+        let start = map.tile_idx(sx as i32, sy as i32);
+        assert!(map.tiles[start].tile_type == RegionTileType::Floor);
+
+        let mut seen = vec![false; map.tiles.len()];
+        let mut open = vec![(sx as i32, sy as i32)];
+        seen[start] = true;
+        while let Some((x, y)) = open.pop() {
+            for i in 0..4 {
+                let direction = Direction::from(i);
+                let (dx, dy) = direction.delta_forward();
+                let idx = map.tile_idx(x + dx, y + dy);
+                if map.can_move(x, y, direction) && !seen[idx] {
+                    seen[idx] = true;
+                    open.push((x + dx, y + dy));
+                }
+            }
+        }
+
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            if tile.tile_type == RegionTileType::Floor {
+                assert!(seen[idx], "Tile {} can't be reached", idx);
+            }
+        }
+    }
+
+    #[test]
+    fn every_algorithm_is_connected() {
+        for algorithm in GeneratorAlgorithm::ALL {
+            for seed in 1..20 {
+                let mut settings = GeneratorSettings::default();
+                settings.algorithm = algorithm;
+                settings.seed = seed;
+                let map = generate_map(&settings);
+                assert_eq!(map.tiles.len(), 32 * 32);
+                assert_connected(&map);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_map() {
+        for algorithm in GeneratorAlgorithm::ALL {
+            let mut settings = GeneratorSettings::default();
+            settings.algorithm = algorithm;
+            settings.seed = 42;
+            let a = generate_map(&settings);
+            let b = generate_map(&settings);
+            assert!(a.tiles == b.tiles);
+        }
+    }
+
+    #[test]
+    fn tiny_maps_still_work() {
+        for algorithm in GeneratorAlgorithm::ALL {
+            let mut settings = GeneratorSettings::default();
+            settings.algorithm = algorithm;
+            settings.size = (2, 3);
+            assert_connected(&generate_map(&settings));
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Cell, GeneratorSettings, Grid};
+use bracket_random::prelude::RandomNumberGenerator;
+
+#[derive(Clone, Copy)]
+struct Room {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+}
+
+impl Room {
+    fn center(&self) -> (i32, i32) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Rooms keep a tile of rock between them.
+    fn overlaps(&self, other: &Room) -> bool {
+        self.x <= other.x + other.w
+            && self.x + self.w >= other.x
+            && self.y <= other.y + other.h
+            && self.y + self.h >= other.y
+    }
+}
+
+/// Scatters non-overlapping rooms, joining each to the previous one with
+/// an L-shaped corridor. Returns the grid and the middle of the first room.
+pub(super) fn build(
+    size: (u32, u32),
+    settings: &GeneratorSettings,
+    rng: &mut RandomNumberGenerator,
+) -> (Grid, (u32, u32)) {
+    let mut grid = Grid::new(size, Cell::Rock);
+    let (width, height) = (size.0 as i32, size.1 as i32);
+    let min_size = i32::max(settings.room_size.0 as i32, 1);
+    let max_size = i32::max(settings.room_size.1 as i32, min_size);
+
+    let mut rooms: Vec<Room> = Vec::new();
+    for _ in 0..settings.max_rooms {
+        let w = i32::min(rng.range(min_size, max_size + 1), width);
+        let h = i32::min(rng.range(min_size, max_size + 1), height);
+        let room = Room {
+            x: rng.range(0, width - w + 1),
+            y: rng.range(0, height - h + 1),
+            w,
+            h,
+        };
+        if rooms.iter().any(|other| room.overlaps(other)) {
+            continue;
+        }
+        carve_room(&mut grid, &room);
+        if let Some(previous) = rooms.last() {
+            let (from, to) = (previous.center(), room.center());
+            if rng.range(0, 2) == 0 {
+                corridor(&mut grid, from, (to.0, from.1));
+                corridor(&mut grid, (to.0, from.1), to);
+            } else {
+                corridor(&mut grid, from, (from.0, to.1));
+                corridor(&mut grid, (from.0, to.1), to);
+            }
+        }
+        rooms.push(room);
+    }
+
+    // Nothing fitted, so the whole map is one room
+    if rooms.is_empty() {
+        let room = Room {
+            x: 0,
+            y: 0,
+            w: width,
+            h: height,
+        };
+        carve_room(&mut grid, &room);
+        rooms.push(room);
+    }
+
+    let (sx, sy) = rooms[0].center();
+    (grid, (sx as u32, sy as u32))
+}
+
+fn carve_room(grid: &mut Grid, room: &Room) {
+    for y in room.y..room.y + room.h {
+        for x in room.x..room.x + room.w {
+            grid.set(x, y, Cell::Room);
+        }
+    }
+}
+
+/// Digs a straight corridor. Wherever it passes between a room and the
+/// corridor itself, that edge becomes a doorway.
+fn corridor(grid: &mut Grid, from: (i32, i32), to: (i32, i32)) {
+    let step = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let mut current = from;
+    while current != to {
+        let next = (current.0 + step.0, current.1 + step.1);
+        if grid.get(next.0, next.1) == Cell::Rock {
+            grid.set(next.0, next.1, Cell::Corridor);
+        }
+        grid.add_doorway(current, next);
+        current = next;
+    }
+}
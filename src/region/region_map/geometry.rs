@@ -1,4 +1,5 @@
 use super::material_bucket::Bucket;
+use crate::module::Direction;
 
 pub const GEOMETRY_SIZE: f32 = 10.0;
 
@@ -703,3 +704,80 @@ pub fn add_west_facing_opening_geometry(
     add_west_facing_wall_geometry(vertices, normals, uv, tangents, x, y, z, w, h / 4.0);
     add_west_facing_wall_geometry(vertices, normals, uv, tangents, x, y + 0.75, z, w, h / 4.0);
 }
+
+/// A door frame, shaped like an opening. Closed doors fill the middle of
+/// the frame with a door panel.
+pub fn add_door_geometry(bucket: &mut Bucket, facing: Direction, x: f32, y: f32, open: bool) {
+    let Bucket {
+        vertices,
+        normals,
+        uv,
+        tangents,
+    } = bucket;
+    match facing {
+        Direction::South => {
+            add_south_facing_opening_geometry(vertices, normals, uv, tangents, x, y, 0.0, 1.0, 1.0);
+            if !open {
+                add_south_facing_wall_geometry(
+                    vertices,
+                    normals,
+                    uv,
+                    tangents,
+                    x + 0.25,
+                    y,
+                    0.0,
+                    0.5,
+                    1.0,
+                );
+            }
+        }
+        Direction::North => {
+            add_north_facing_opening_geometry(vertices, normals, uv, tangents, x, y, 0.0, 1.0, 1.0);
+            if !open {
+                add_north_facing_wall_geometry(
+                    vertices,
+                    normals,
+                    uv,
+                    tangents,
+                    x + 0.25,
+                    y,
+                    0.0,
+                    0.5,
+                    1.0,
+                );
+            }
+        }
+        Direction::East => {
+            add_east_facing_opening_geometry(vertices, normals, uv, tangents, x, y, 0.0, 1.0, 1.0);
+            if !open {
+                add_east_facing_wall_geometry(
+                    vertices,
+                    normals,
+                    uv,
+                    tangents,
+                    x,
+                    y + 0.25,
+                    0.0,
+                    1.0,
+                    0.5,
+                );
+            }
+        }
+        Direction::West => {
+            add_west_facing_opening_geometry(vertices, normals, uv, tangents, x, y, 0.0, 1.0, 1.0);
+            if !open {
+                add_west_facing_wall_geometry(
+                    vertices,
+                    normals,
+                    uv,
+                    tangents,
+                    x,
+                    y + 0.25,
+                    0.0,
+                    1.0,
+                    0.5,
+                );
+            }
+        }
+    }
+}
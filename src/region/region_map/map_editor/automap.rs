@@ -0,0 +1,81 @@
+use super::{
+    render_strokes::RenderStrokes,
+    scaling::Scaling,
+    wall_lines::{wall_door, wall_line, wall_opening},
+};
+use crate::{
+    module::{Direction, MaterialDefinition},
+    region::region_map::{ExploredMap, RegionBoundaryType, RegionMap, RegionTileType},
+};
+use bevy_egui::egui::{Pos2, Rect, Sense, Ui};
+use std::collections::HashMap;
+
+/// Draws the parts of a map the party has seen, with an arrow for the
+/// party itself.
+pub fn automap_ui(
+    ui: &mut Ui,
+    map: &RegionMap,
+    explored: &ExploredMap,
+    player: (i32, i32, Direction),
+    mats: &HashMap<usize, (String, MaterialDefinition, String)>,
+) {
+    let (response, painter) = ui.allocate_painter(ui.available_size_before_wrap(), Sense::hover());
+    let strokes = RenderStrokes::default(mats);
+    let scale = Scaling::new(&response, map);
+
+    for y in 0..map.size.1 {
+        for x in 0..map.size.0 {
+            let idx = map.tile_idx(x as i32, y as i32);
+            let tile = &map.tiles[idx];
+
+            if explored.tile_seen(idx) && tile.tile_type == RegionTileType::Floor {
+                let min = Pos2::new(x as f32 * scale.box_x, y as f32 * scale.box_y);
+                let max = Pos2::new((x + 1) as f32 * scale.box_x, (y + 1) as f32 * scale.box_y);
+                painter.rect_filled(
+                    Rect::from_two_pos(scale.to_screen * min, scale.to_screen * max),
+                    0.0,
+                    strokes
+                        .fill(tile.floor_material as usize)
+                        .linear_multiply(0.25),
+                );
+            }
+
+            for dir in 0..4 {
+                let direction: Direction = dir.into();
+                if !explored.wall_seen(idx, direction) {
+                    continue;
+                }
+                let stroke = strokes.wall_type(tile.boundaries[dir]);
+                match tile.boundaries[dir].0 {
+                    RegionBoundaryType::Wall => {
+                        painter.line_segment(wall_line(direction, x, y, &scale), stroke);
+                    }
+                    RegionBoundaryType::Opening => {
+                        for segment in wall_opening(direction, x, y, &scale) {
+                            painter.line_segment(segment, stroke);
+                        }
+                    }
+                    RegionBoundaryType::Door => {
+                        for segment in wall_opening(direction, x, y, &scale) {
+                            painter.line_segment(segment, stroke);
+                        }
+                        painter.line_segment(
+                            wall_door(direction, x, y, &scale),
+                            strokes.door(map.door_state(x as i32, y as i32, direction)),
+                        );
+                    }
+                    RegionBoundaryType::None => {}
+                }
+            }
+        }
+    }
+
+    let (x, y, facing) = player;
+    let px = (x as f32 * scale.box_x) + (scale.box_x / 2.0);
+    let py = (y as f32 * scale.box_y) + (scale.box_y / 2.0);
+    painter.arrow(
+        scale.to_screen * Pos2 { x: px, y: py },
+        facing.to_direction_vec2(10.0),
+        strokes.highlight,
+    );
+}
@@ -1,16 +1,18 @@
 use super::{
     render_strokes::RenderStrokes,
     scaling::Scaling,
+    tools::set_edge,
     wall_interaction::MapWallInteraction,
-    wall_lines::{wall_line, wall_opening},
-    MapEditorMode, MapEditorSettings,
+    wall_lines::{wall_door, wall_line, wall_opening},
+    EditHistory, MapEditorMode, MapEditorSettings,
 };
 use crate::{
-    module::{Direction, MaterialDefinition, Module},
-    region::region_map::{RegionBoundaryType, RegionMap, RegionTileType},
+    module::{game_events::GameEvent, Direction, MaterialDefinition, Module},
+    region::region_map::{DoorState, RegionBoundaryType, RegionMap, RegionTile, RegionTileType},
 };
 use bevy_egui::egui::{
-    Color32, CtxRef, Frame, Painter, PointerButton, Pos2, Response, Sense, Stroke, Ui, Window,
+    Button, CollapsingHeader, Color32, ComboBox, CtxRef, Frame, Key, Painter, PointerButton, Pos2,
+    Response, ScrollArea, Sense, Stroke, Ui, Window,
 };
 use std::collections::HashMap;
 
@@ -23,14 +25,76 @@ impl<'a> MapEditor<'a> {
     pub fn render_in_module(
         ctx: &CtxRef,
         editor_settings: &mut MapEditorSettings,
+        history: &mut EditHistory,
         module: &mut Module,
         map_id: usize,
     ) {
+        // Ctrl+Z / Ctrl+Y (or Ctrl+Shift+Z), unless a text field has the keyboard
+        if !ctx.wants_keyboard_input() {
+            let input = ctx.input();
+            if input.modifiers.command && input.key_pressed(Key::Z) {
+                if input.modifiers.shift {
+                    history.redo(&mut module.maps);
+                } else {
+                    history.undo(&mut module.maps);
+                }
+            } else if input.modifiers.command && input.key_pressed(Key::Y) {
+                history.redo(&mut module.maps);
+            }
+        }
+
+        // Edits only happen when a click or drag finishes, or while typing,
+        // so only snapshot the map then.
+        let typing = ctx.wants_keyboard_input();
+        let before = if typing || ctx.input().pointer.any_released() {
+            Some(module.maps[&map_id].clone())
+        } else {
+            None
+        };
+        let mut jump_to = None;
+
+        let mut event_tags: Vec<String> =
+            module.events.events.iter().map(|e| e.tag.clone()).collect();
+        event_tags.sort();
+        let mut new_event = None;
+
         let map = module.maps.get_mut(&map_id).unwrap();
         let mats = module.materials.clone();
         Window::new(format!("Map: {}", map.name))
             .default_size(bevy_egui::egui::vec2(512.0, 512.0))
             .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(history.can_undo(), Button::new("Undo"))
+                        .clicked()
+                    {
+                        jump_to = Some(history.position() - 1);
+                    }
+                    if ui
+                        .add_enabled(history.can_redo(), Button::new("Redo"))
+                        .clicked()
+                    {
+                        jump_to = Some(history.position() + 1);
+                    }
+                    CollapsingHeader::new("History").show(ui, |ui| {
+                        ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                            if ui
+                                .selectable_label(history.position() == 0, "Original map")
+                                .clicked()
+                            {
+                                jump_to = Some(0);
+                            }
+                            for (i, description) in history.descriptions().enumerate() {
+                                if ui
+                                    .selectable_label(history.position() == i + 1, description)
+                                    .clicked()
+                                {
+                                    jump_to = Some(i + 1);
+                                }
+                            }
+                        });
+                    });
+                });
                 ui.horizontal(|ui| {
                     ui.label("Mode:");
                     ui.radio_value(&mut editor_settings.mode, MapEditorMode::Walls, "Wall");
@@ -39,6 +103,20 @@ impl<'a> MapEditor<'a> {
                     ui.radio_value(&mut editor_settings.mode, MapEditorMode::Start, "Start");
                     ui.radio_value(&mut editor_settings.mode, MapEditorMode::Opening, "Opening");
                     ui.radio_value(&mut editor_settings.mode, MapEditorMode::Trigger, "Trigger");
+                    ui.radio_value(&mut editor_settings.mode, MapEditorMode::Door, "Door");
+                    ui.radio_value(&mut editor_settings.mode, MapEditorMode::Solid, "Solid");
+                    ui.radio_value(&mut editor_settings.mode, MapEditorMode::Inspect, "Inspect");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Tools:");
+                    for mode in [
+                        MapEditorMode::Room,
+                        MapEditorMode::Line,
+                        MapEditorMode::FillFloor,
+                        MapEditorMode::FillCeiling,
+                    ] {
+                        ui.radio_value(&mut editor_settings.mode, mode, mode.name());
+                    }
                 });
                 ui.checkbox(&mut editor_settings.fill_walls, "Double-Sided Walls");
 
@@ -51,6 +129,17 @@ impl<'a> MapEditor<'a> {
                         }
                     });
 
+                match editor_settings.mode {
+                    MapEditorMode::Door => door_properties(ui, editor_settings, map),
+                    MapEditorMode::Trigger => {
+                        new_event = trigger_properties(ui, editor_settings, map, &event_tags)
+                    }
+                    MapEditorMode::Inspect => {
+                        new_event = tile_inspector(ui, editor_settings, map, &mats, &event_tags)
+                    }
+                    _ => {}
+                }
+
                 ui.separator();
                 ui.text_edit_singleline(&mut map.name);
                 Frame::dark_canvas(ui.style()).show(ui, |ui| {
@@ -61,6 +150,32 @@ impl<'a> MapEditor<'a> {
                     ed.ui_content(ui, &mats)
                 });
             });
+
+        if let Some(tag) = new_event {
+            module.events.events.push(GameEvent {
+                tag,
+                steps: Vec::new(),
+                filename: module.events.filename.clone(),
+            });
+        }
+
+        if let Some(before) = before {
+            let description = if typing && editor_settings.mode == MapEditorMode::Door {
+                "Door properties"
+            } else {
+                editor_settings.mode.name()
+            };
+            history.record(
+                description.to_string(),
+                map_id,
+                &before,
+                &module.maps[&map_id],
+                typing,
+            );
+        }
+        if let Some(position) = jump_to {
+            history.jump_to(position, &mut module.maps);
+        }
     }
 
     fn ui_content(
@@ -68,25 +183,39 @@ impl<'a> MapEditor<'a> {
         ui: &mut Ui,
         mats: &HashMap<usize, (String, MaterialDefinition, String)>,
     ) -> bevy_egui::egui::Response {
-        let (response, painter) = ui.allocate_painter(
-            ui.available_size_before_wrap(),
-            Sense::union(Sense::click(), Sense::hover()),
-        );
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
 
         let strokes = RenderStrokes::default(mats);
         let scale = Scaling::new(&response, self.map);
 
         self.draw_base_map(&painter, &scale, &strokes);
 
-        if let Some(pointer_pos) = response.hover_pos() {
+        if matches!(
+            self.settings.mode,
+            MapEditorMode::Room | MapEditorMode::Line
+        ) {
+            self.drag_interact(&scale, &strokes, &painter, &response);
+        } else if let Some(pointer_pos) = response.hover_pos() {
             match self.settings.mode {
-                MapEditorMode::Walls | MapEditorMode::Opening => {
+                MapEditorMode::Walls | MapEditorMode::Opening | MapEditorMode::Door => {
                     self.wall_interact(&scale, &strokes, pointer_pos, &painter, &response)
                 }
                 MapEditorMode::Floor => self.floor_interact(&scale, pointer_pos, &response),
                 MapEditorMode::Ceiling => self.ceiling_interact(&scale, pointer_pos, &response),
                 MapEditorMode::Start => self.start_interact(&scale, pointer_pos, &response),
                 MapEditorMode::Trigger => self.trigger_interact(&scale, pointer_pos, &response),
+                MapEditorMode::Solid => self.solid_interact(&scale, pointer_pos, &response),
+                MapEditorMode::Inspect => {
+                    if response.clicked_by(PointerButton::Primary) {
+                        let pos = MapWallInteraction::new(&scale, pointer_pos, self.map);
+                        self.settings.selected_tile = Some((pos.tile_x, pos.tile_y));
+                    }
+                }
+                MapEditorMode::FillFloor | MapEditorMode::FillCeiling => {
+                    self.fill_interact(&scale, pointer_pos, &response)
+                }
+                MapEditorMode::Room | MapEditorMode::Line => {}
             }
         }
 
@@ -114,6 +243,20 @@ impl<'a> MapEditor<'a> {
                                     );
                                 });
                         }
+                        RegionBoundaryType::Door => {
+                            wall_opening(direction, x, y, scale)
+                                .iter()
+                                .for_each(|segment| {
+                                    painter.line_segment(
+                                        *segment,
+                                        strokes.wall_type(tile.boundaries[dir]),
+                                    );
+                                });
+                            painter.line_segment(
+                                wall_door(direction, x, y, scale),
+                                strokes.door(self.map.door_state(x as i32, y as i32, direction)),
+                            );
+                        }
                         RegionBoundaryType::None | RegionBoundaryType::Wall => {
                             painter.line_segment(
                                 wall_line(direction, x, y, scale),
@@ -123,9 +266,32 @@ impl<'a> MapEditor<'a> {
                     }
                 }
 
+                // Solid tiles are shown in every mode
+                if tile.tile_type == RegionTileType::Solid {
+                    painter.rect(
+                        bevy_egui::egui::Rect::from_two_pos(
+                            scale.to_screen
+                                * Pos2 {
+                                    x: x as f32 * scale.box_x,
+                                    y: y as f32 * scale.box_y,
+                                },
+                            scale.to_screen
+                                * Pos2 {
+                                    x: (x + 1) as f32 * scale.box_x,
+                                    y: (y + 1) as f32 * scale.box_y,
+                                },
+                        ),
+                        0.0,
+                        strokes.fill(tile.floor_material as usize),
+                        Stroke::new(2.0, Color32::BLACK),
+                    );
+                }
+
                 // Display Floors
-                if self.settings.mode == MapEditorMode::Floor
-                    && tile.tile_type == RegionTileType::Floor
+                if matches!(
+                    self.settings.mode,
+                    MapEditorMode::Floor | MapEditorMode::FillFloor | MapEditorMode::Room
+                ) && tile.tile_type == RegionTileType::Floor
                 {
                     let px = x as f32 * scale.box_x;
                     let py = y as f32 * scale.box_y;
@@ -151,7 +317,11 @@ impl<'a> MapEditor<'a> {
                 }
 
                 // Display Ceilings
-                if self.settings.mode == MapEditorMode::Ceiling && tile.has_ceiling {
+                if matches!(
+                    self.settings.mode,
+                    MapEditorMode::Ceiling | MapEditorMode::FillCeiling
+                ) && tile.has_ceiling
+                {
                     let px = x as f32 * scale.box_x;
                     let py = y as f32 * scale.box_y;
                     let x20 = scale.x10 * 8.0;
@@ -189,6 +359,41 @@ impl<'a> MapEditor<'a> {
                     );
                 }
 
+                // Triggers: a dot for entering, an arrow for leaving
+                let center = scale.to_screen
+                    * Pos2 {
+                        x: (x as f32 * scale.box_x) + (scale.box_x / 2.0),
+                        y: (y as f32 * scale.box_y) + (scale.box_y / 2.0),
+                    };
+                if tile.entry_trigger.is_some() {
+                    painter.circle_filled(center, 3.0, strokes.trigger.color);
+                }
+                if let Some((direction, _)) = &tile.exit_trigger {
+                    painter.arrow(center, direction.to_direction_vec2(8.0), strokes.trigger);
+                }
+                if matches!(
+                    self.settings.mode,
+                    MapEditorMode::Trigger | MapEditorMode::Inspect
+                ) && self.settings.selected_tile == Some((x, y))
+                {
+                    painter.rect_stroke(
+                        bevy_egui::egui::Rect::from_two_pos(
+                            scale.to_screen
+                                * Pos2 {
+                                    x: x as f32 * scale.box_x,
+                                    y: y as f32 * scale.box_y,
+                                },
+                            scale.to_screen
+                                * Pos2 {
+                                    x: (x + 1) as f32 * scale.box_x,
+                                    y: (y + 1) as f32 * scale.box_y,
+                                },
+                        ),
+                        0.0,
+                        strokes.highlight,
+                    );
+                }
+
                 // Current player position
                 if let Some((x, y, facing)) = self.settings.highlight_player {
                     let px = (x as f32 * scale.box_x) + (scale.box_x / 2.0);
@@ -218,11 +423,17 @@ impl<'a> MapEditor<'a> {
         }
     }
 
+    /// Left click selects a tile for the trigger inspector, right click
+    /// clears its triggers.
     fn trigger_interact(&mut self, scale: &Scaling, pointer_pos: Pos2, response: &Response) {
+        let pos = MapWallInteraction::new(scale, pointer_pos, self.map);
         if response.clicked_by(PointerButton::Primary) {
-            let pos = MapWallInteraction::new(scale, pointer_pos, self.map);
+            self.settings.selected_tile = Some((pos.tile_x, pos.tile_y));
+        }
+        if response.clicked_by(PointerButton::Secondary) {
             let tile_idx = ((self.map.size.0 * pos.tile_y) + pos.tile_x) as usize;
-            self.map.tiles[tile_idx].entry_trigger = Some("NewTrigger".to_string());
+            self.map.tiles[tile_idx].entry_trigger = None;
+            self.map.tiles[tile_idx].exit_trigger = None;
         }
     }
 
@@ -242,6 +453,22 @@ impl<'a> MapEditor<'a> {
         }
     }
 
+    /// Left click fills the tile with a solid block of the current
+    /// material, right click empties it.
+    fn solid_interact(&mut self, scale: &Scaling, pointer_pos: Pos2, response: &Response) {
+        let pos = MapWallInteraction::new(scale, pointer_pos, self.map);
+        let tile_idx = ((self.map.size.0 * pos.tile_y) + pos.tile_x) as usize;
+        if response.clicked_by(PointerButton::Primary) {
+            self.map.tiles[tile_idx].floor_material = self.settings.material as u32;
+            self.map.tiles[tile_idx].tile_type = RegionTileType::Solid;
+            self.map.needs_rebuild = true;
+        }
+        if response.clicked_by(PointerButton::Secondary) {
+            self.map.tiles[tile_idx].tile_type = RegionTileType::Empty;
+            self.map.needs_rebuild = true;
+        }
+    }
+
     fn ceiling_interact(&mut self, scale: &Scaling, pointer_pos: Pos2, response: &Response) {
         if response.clicked_by(PointerButton::Primary) {
             let pos = MapWallInteraction::new(scale, pointer_pos, self.map);
@@ -283,29 +510,34 @@ impl<'a> MapEditor<'a> {
 
     fn wall_interact_click(&mut self, x: u32, y: u32, boundary: usize, response: &Response) {
         let tile_idx = ((self.map.size.0 * y) + x) as usize;
+        let new_wall = match self.settings.mode {
+            MapEditorMode::Opening => RegionBoundaryType::Opening,
+            MapEditorMode::Door => RegionBoundaryType::Door,
+            _ => RegionBoundaryType::Wall,
+        };
         if response.clicked_by(PointerButton::Primary) {
-            self.map.tiles[tile_idx].boundaries[boundary].0 = match self.settings.mode {
-                MapEditorMode::Opening => RegionBoundaryType::Opening,
-                _ => RegionBoundaryType::Wall,
-            };
+            if new_wall == RegionBoundaryType::Door {
+                let direction: Direction = boundary.into();
+                self.settings.selected_door =
+                    Some(self.map.door_edge(x as i32, y as i32, direction));
+                if self.map.is_door(x as i32, y as i32, direction) {
+                    // Clicking an existing door selects it for editing
+                    return;
+                }
+            }
+            self.map.tiles[tile_idx].boundaries[boundary].0 = new_wall;
             self.map.tiles[tile_idx].boundaries[boundary].1 = self.settings.material as u32;
             if self.settings.fill_walls {
-                self.wall_reciprocal_click(
-                    x,
-                    y,
-                    boundary,
-                    match self.settings.mode {
-                        MapEditorMode::Opening => RegionBoundaryType::Opening,
-                        _ => RegionBoundaryType::Wall,
-                    },
-                );
+                self.wall_reciprocal_click(x, y, boundary, new_wall);
             }
+            self.map.prune_doors();
             self.map.needs_rebuild = true;
         } else if response.clicked_by(PointerButton::Secondary) {
             self.map.tiles[tile_idx].boundaries[boundary].0 = RegionBoundaryType::None;
             if self.settings.fill_walls {
                 self.wall_reciprocal_click(x, y, boundary, RegionBoundaryType::None);
             }
+            self.map.prune_doors();
             self.map.needs_rebuild = true;
         }
     }
@@ -340,3 +572,234 @@ impl<'a> MapEditor<'a> {
         }
     }
 }
+
+/// Initial state, key and script for the selected door.
+fn door_properties(ui: &mut Ui, settings: &mut MapEditorSettings, map: &mut RegionMap) {
+    let (x, y, direction) = match settings.selected_door {
+        Some(door) if map.is_door(door.0 as i32, door.1 as i32, door.2) => door,
+        _ => {
+            ui.label("Click a wall to place a door, or a door to edit it.");
+            return;
+        }
+    };
+
+    let door = map.door_mut(x as i32, y as i32, direction);
+    let mut state = door.state;
+    let mut key = door.key.clone().unwrap_or_default();
+    let mut on_open = door.on_open.clone().unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label(format!("Door at {},{} ({:?})", x, y, direction));
+        for s in [DoorState::Open, DoorState::Closed, DoorState::Locked] {
+            ui.radio_value(&mut state, s, s.name());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Key item:");
+        ui.text_edit_singleline(&mut key);
+        ui.label("On open:");
+        ui.text_edit_singleline(&mut on_open);
+    });
+
+    door.key = if key.is_empty() { None } else { Some(key) };
+    door.on_open = if on_open.is_empty() {
+        None
+    } else {
+        Some(on_open)
+    };
+    if state != door.state {
+        map.set_door_state(x as i32, y as i32, direction, state);
+    }
+}
+
+/// Entry and exit triggers for the selected tile. Returns the tag of an
+/// event to add to the module, if one was created.
+fn trigger_properties(
+    ui: &mut Ui,
+    settings: &mut MapEditorSettings,
+    map: &mut RegionMap,
+    events: &[String],
+) -> Option<String> {
+    let (x, y) = match selected_tile(settings, map) {
+        Some(tile) => tile,
+        None => {
+            ui.label("Click a tile to edit its triggers. Right click clears them.");
+            return None;
+        }
+    };
+    ui.label(format!("Tile {},{}", x, y));
+    let idx = map.tile_idx(x as i32, y as i32);
+    trigger_fields(ui, &mut settings.new_event_tag, &mut map.tiles[idx], events)
+}
+
+/// Everything about the selected tile: type, materials, walls and triggers.
+fn tile_inspector(
+    ui: &mut Ui,
+    settings: &mut MapEditorSettings,
+    map: &mut RegionMap,
+    mats: &HashMap<usize, (String, MaterialDefinition, String)>,
+    events: &[String],
+) -> Option<String> {
+    let (x, y) = match selected_tile(settings, map) {
+        Some(tile) => tile,
+        None => {
+            ui.label("Click a tile to inspect it.");
+            return None;
+        }
+    };
+    let idx = map.tile_idx(x as i32, y as i32);
+    let before = map.tiles[idx].clone();
+    let tile = &mut map.tiles[idx];
+
+    ui.horizontal(|ui| {
+        ui.label(format!("Tile {},{}", x, y));
+        for t in [
+            RegionTileType::Empty,
+            RegionTileType::Floor,
+            RegionTileType::Solid,
+        ] {
+            ui.radio_value(&mut tile.tile_type, t, t.name());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Floor:");
+        material_combo(ui, "Floor material", &mut tile.floor_material, mats);
+        ui.checkbox(&mut tile.has_ceiling, "Ceiling:");
+        material_combo(ui, "Ceiling material", &mut tile.ceiling_material, mats);
+    });
+    let mut edges = Vec::new();
+    for direction in Direction::ALL {
+        let before = tile.boundaries[direction.to_exit_index()];
+        let (mut boundary, mut material) = before;
+        ui.horizontal(|ui| {
+            ui.label(format!("{:?}:", direction));
+            ComboBox::from_id_source(format!("{:?} boundary", direction))
+                .selected_text(boundary.name())
+                .show_ui(ui, |ui| {
+                    for b in [
+                        RegionBoundaryType::None,
+                        RegionBoundaryType::Wall,
+                        RegionBoundaryType::Opening,
+                        RegionBoundaryType::Door,
+                    ] {
+                        ui.selectable_value(&mut boundary, b, b.name());
+                    }
+                });
+            material_combo(
+                ui,
+                &format!("{:?} material", direction),
+                &mut material,
+                mats,
+            );
+        });
+        if (boundary, material) != before {
+            edges.push((direction, (boundary, material)));
+        }
+    }
+    let new_event = trigger_fields(ui, &mut settings.new_event_tag, tile, events);
+
+    // Walls are set the same way the drawing tools set them, so the
+    // neighbour's side follows when walls are double-sided.
+    let edges_changed = !edges.is_empty();
+    for (direction, edge) in edges {
+        set_edge(map, x, y, direction, edge, settings.fill_walls);
+    }
+
+    if edges_changed || map.tiles[idx] != before {
+        map.prune_doors();
+        map.needs_rebuild = true;
+    }
+    new_event
+}
+
+fn selected_tile(settings: &MapEditorSettings, map: &RegionMap) -> Option<(u32, u32)> {
+    settings
+        .selected_tile
+        .filter(|(x, y)| map.in_bounds(*x as i32, *y as i32))
+}
+
+fn trigger_fields(
+    ui: &mut Ui,
+    new_event_tag: &mut String,
+    tile: &mut RegionTile,
+    events: &[String],
+) -> Option<String> {
+    ui.horizontal(|ui| {
+        ui.label("On enter:");
+        event_combo(ui, "Entry trigger", &mut tile.entry_trigger, events);
+    });
+
+    let mut exit_tag = tile.exit_trigger.as_ref().map(|(_, tag)| tag.clone());
+    let mut direction = tile
+        .exit_trigger
+        .as_ref()
+        .map_or(Direction::North, |(direction, _)| *direction);
+    ui.horizontal(|ui| {
+        ui.label("On leaving");
+        ComboBox::from_id_source("Exit direction")
+            .selected_text(format!("{:?}", direction))
+            .show_ui(ui, |ui| {
+                for d in Direction::ALL {
+                    ui.selectable_value(&mut direction, d, format!("{:?}", d));
+                }
+            });
+        event_combo(ui, "Exit trigger", &mut exit_tag, events);
+    });
+    tile.exit_trigger = exit_tag.map(|tag| (direction, tag));
+
+    let mut new_event = None;
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(new_event_tag);
+        let tag = new_event_tag.trim().to_string();
+        let available = !tag.is_empty() && !events.contains(&tag);
+        if ui
+            .add_enabled(available, Button::new("New Entry Event"))
+            .clicked()
+        {
+            tile.entry_trigger = Some(tag.clone());
+            new_event = Some(tag.clone());
+        }
+        if ui
+            .add_enabled(available, Button::new("New Exit Event"))
+            .on_hover_text("Fires when leaving in the direction chosen above")
+            .clicked()
+        {
+            tile.exit_trigger = Some((direction, tag.clone()));
+            new_event = Some(tag);
+        }
+    });
+    if new_event.is_some() {
+        new_event_tag.clear();
+    }
+    new_event
+}
+
+fn material_combo(
+    ui: &mut Ui,
+    id: &str,
+    value: &mut u32,
+    mats: &HashMap<usize, (String, MaterialDefinition, String)>,
+) {
+    let mut sorted: Vec<_> = mats.iter().collect();
+    sorted.sort_by_key(|(idx, _)| **idx);
+    ComboBox::from_id_source(id)
+        .selected_text(
+            mats.get(&(*value as usize))
+                .map_or("(missing)".to_string(), |m| m.0.clone()),
+        )
+        .show_ui(ui, |ui| {
+            for (idx, (name, _, _)) in sorted {
+                ui.selectable_value(value, *idx as u32, name.as_str());
+            }
+        });
+}
+
+fn event_combo(ui: &mut Ui, id: &str, value: &mut Option<String>, events: &[String]) {
+    ComboBox::from_id_source(id)
+        .selected_text(value.clone().unwrap_or_else(|| "(none)".to_string()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, "(none)");
+            for tag in events.iter() {
+                ui.selectable_value(value, Some(tag.clone()), tag.as_str());
+            }
+        });
+}
@@ -6,4 +6,31 @@ pub enum MapEditorMode {
     Start,
     Opening,
     Trigger,
+    Door,
+    Solid,
+    Inspect,
+    Room,
+    Line,
+    FillFloor,
+    FillCeiling,
+}
+
+impl MapEditorMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapEditorMode::Walls => "Wall",
+            MapEditorMode::Floor => "Floor",
+            MapEditorMode::Ceiling => "Ceiling",
+            MapEditorMode::Start => "Start",
+            MapEditorMode::Opening => "Opening",
+            MapEditorMode::Trigger => "Trigger",
+            MapEditorMode::Door => "Door",
+            MapEditorMode::Solid => "Solid",
+            MapEditorMode::Inspect => "Inspect",
+            MapEditorMode::Room => "Room",
+            MapEditorMode::Line => "Wall Line",
+            MapEditorMode::FillFloor => "Fill Floor",
+            MapEditorMode::FillCeiling => "Fill Ceiling",
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use super::editor_mode::MapEditorMode;
+use super::{editor_mode::MapEditorMode, tools::ToolDrag};
 use crate::module::Direction;
 
 #[derive(Clone)]
@@ -7,6 +7,14 @@ pub struct MapEditorSettings {
     pub fill_walls: bool,
     pub material: usize,
     pub highlight_player: Option<(i32, i32, Direction)>,
+    /// Door whose properties are shown in Door mode
+    pub selected_door: Option<(u32, u32, Direction)>,
+    /// Room or wall line in progress
+    pub drag: Option<ToolDrag>,
+    /// Tile shown in Trigger and Inspect modes
+    pub selected_tile: Option<(u32, u32)>,
+    /// Tag typed in for a new trigger event
+    pub new_event_tag: String,
 }
 
 impl MapEditorSettings {
@@ -16,6 +24,10 @@ impl MapEditorSettings {
             fill_walls: true,
             material: 0,
             highlight_player: None,
+            selected_door: None,
+            drag: None,
+            selected_tile: None,
+            new_event_tag: String::new(),
         }
     }
 }
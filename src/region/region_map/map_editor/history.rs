@@ -0,0 +1,237 @@
+use crate::{
+    module::Direction,
+    region::region_map::{Door, RegionMap, RegionTile},
+};
+use std::collections::HashMap;
+
+/// Older edits are forgotten once the history is this long.
+const MAX_HISTORY: usize = 200;
+
+/// One undoable change to a map, stored as the parts of the map that
+/// differ before and after it.
+#[derive(Clone)]
+struct MapEdit {
+    description: String,
+    map_idx: usize,
+    /// Tile index, before, after
+    tiles: Vec<(usize, RegionTile, RegionTile)>,
+    doors: Option<(Vec<Door>, Vec<Door>)>,
+    start: Option<((u32, u32, Direction), (u32, u32, Direction))>,
+    /// Typing into a text field merges into the previous edit instead of
+    /// adding one per key.
+    mergeable: bool,
+}
+
+impl MapEdit {
+    fn diff(description: String, map_idx: usize, before: &RegionMap, after: &RegionMap) -> Self {
+        let tiles = before
+            .tiles
+            .iter()
+            .zip(after.tiles.iter())
+            .enumerate()
+            .filter(|(_, (b, a))| b != a)
+            .map(|(idx, (b, a))| (idx, b.clone(), a.clone()))
+            .collect();
+        let doors = if before.doors != after.doors {
+            Some((before.doors.clone(), after.doors.clone()))
+        } else {
+            None
+        };
+        let start = if before.starting_location != after.starting_location {
+            Some((before.starting_location, after.starting_location))
+        } else {
+            None
+        };
+        Self {
+            description,
+            map_idx,
+            tiles,
+            doors,
+            start,
+            mergeable: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tiles.is_empty() && self.doors.is_none() && self.start.is_none()
+    }
+
+    /// Folds a later edit into this one, keeping the earliest "before".
+    fn merge(&mut self, later: MapEdit) {
+        for (idx, before, after) in later.tiles {
+            match self.tiles.iter_mut().find(|(i, _, _)| *i == idx) {
+                Some(tile) => tile.2 = after,
+                None => self.tiles.push((idx, before, after)),
+            }
+        }
+        if let Some((before, after)) = later.doors {
+            self.doors = Some((self.doors.take().map_or(before, |d| d.0), after));
+        }
+        if let Some((before, after)) = later.start {
+            self.start = Some((self.start.map_or(before, |s| s.0), after));
+        }
+    }
+
+    fn apply(&self, map: &mut RegionMap, undo: bool) {
+        for (idx, before, after) in self.tiles.iter() {
+            if let Some(tile) = map.tiles.get_mut(*idx) {
+                *tile = if undo { before.clone() } else { after.clone() };
+            }
+        }
+        if let Some((before, after)) = &self.doors {
+            map.doors = if undo { before.clone() } else { after.clone() };
+        }
+        if let Some((before, after)) = self.start {
+            map.starting_location = if undo { before } else { after };
+        }
+        map.needs_rebuild = true;
+    }
+}
+
+/// Undo/redo history for map edits, shared by every map in a module.
+#[derive(Default)]
+pub struct EditHistory {
+    edits: Vec<MapEdit>,
+    /// Number of edits currently applied. Anything after it can be redone.
+    position: usize,
+}
+
+impl EditHistory {
+    /// Records the difference between two versions of a map, if there is
+    /// one. Recording discards anything that could have been redone.
+    pub fn record(
+        &mut self,
+        description: String,
+        map_idx: usize,
+        before: &RegionMap,
+        after: &RegionMap,
+        mergeable: bool,
+    ) {
+        let mut edit = MapEdit::diff(description, map_idx, before, after);
+        if edit.is_empty() {
+            return;
+        }
+        edit.mergeable = mergeable;
+        self.edits.truncate(self.position);
+
+        if let Some(last) = self.edits.last_mut() {
+            if mergeable
+                && last.mergeable
+                && last.map_idx == map_idx
+                && last.description == edit.description
+            {
+                last.merge(edit);
+                return;
+            }
+        }
+
+        self.edits.push(edit);
+        if self.edits.len() > MAX_HISTORY {
+            self.edits.remove(0);
+        }
+        self.position = self.edits.len();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.edits.len()
+    }
+
+    pub fn undo(&mut self, maps: &mut HashMap<usize, RegionMap>) {
+        if !self.can_undo() {
+            return;
+        }
+        self.position -= 1;
+        let edit = &self.edits[self.position];
+        if let Some(map) = maps.get_mut(&edit.map_idx) {
+            edit.apply(map, true);
+        }
+    }
+
+    pub fn redo(&mut self, maps: &mut HashMap<usize, RegionMap>) {
+        if !self.can_redo() {
+            return;
+        }
+        let edit = &self.edits[self.position];
+        if let Some(map) = maps.get_mut(&edit.map_idx) {
+            edit.apply(map, false);
+        }
+        self.position += 1;
+    }
+
+    /// Undoes or redoes until `position` edits are applied.
+    pub fn jump_to(&mut self, position: usize, maps: &mut HashMap<usize, RegionMap>) {
+        while self.position > position && self.can_undo() {
+            self.undo(maps);
+        }
+        while self.position < position && self.can_redo() {
+            self.redo(maps);
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Descriptions of every edit, oldest first.
+    pub fn descriptions(&self) -> impl Iterator<Item = &str> {
+        self.edits.iter().map(|e| e.description.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::region_map::RegionTileType;
+
+    fn maps() -> HashMap<usize, RegionMap> {
+        let mut maps = HashMap::new();
+        maps.insert(0, RegionMap::default());
+        maps
+    }
+
+    fn paint(history: &mut EditHistory, maps: &mut HashMap<usize, RegionMap>, idx: usize) {
+        let before = maps[&0].clone();
+        let map = maps.get_mut(&0).unwrap();
+        map.tiles[idx].tile_type = RegionTileType::Solid;
+        history.record(format!("Paint {}", idx), 0, &before, map, false);
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut maps = maps();
+        let mut history = EditHistory::default();
+        paint(&mut history, &mut maps, 1);
+        paint(&mut history, &mut maps, 2);
+
+        history.undo(&mut maps);
+        assert!(maps[&0].tiles[2].tile_type == RegionTileType::Floor);
+        assert!(maps[&0].tiles[1].tile_type == RegionTileType::Solid);
+
+        history.redo(&mut maps);
+        assert!(maps[&0].tiles[2].tile_type == RegionTileType::Solid);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn recording_discards_redo() {
+        let mut maps = maps();
+        let mut history = EditHistory::default();
+        paint(&mut history, &mut maps, 1);
+        history.undo(&mut maps);
+        paint(&mut history, &mut maps, 3);
+        assert!(!history.can_redo());
+        assert_eq!(history.descriptions().collect::<Vec<_>>(), vec!["Paint 3"]);
+    }
+
+    #[test]
+    fn unchanged_maps_are_not_recorded() {
+        let maps = maps();
+        let mut history = EditHistory::default();
+        history.record("Nothing".to_string(), 0, &maps[&0], &maps[&0], false);
+        assert!(!history.can_undo());
+    }
+}
@@ -1,10 +1,15 @@
+mod automap;
+pub use automap::automap_ui;
 mod editor_mode;
 pub use editor_mode::MapEditorMode;
 mod editor_settings;
 pub use editor_settings::MapEditorSettings;
 mod editor;
+mod history;
+pub use history::EditHistory;
 mod render_strokes;
 mod scaling;
+mod tools;
 mod wall_interaction;
 mod wall_lines;
 pub use editor::MapEditor;
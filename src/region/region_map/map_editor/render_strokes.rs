@@ -1,4 +1,7 @@
-use crate::{module::MaterialDefinition, region::region_map::RegionBoundaryType};
+use crate::{
+    module::MaterialDefinition,
+    region::region_map::{DoorState, RegionBoundaryType},
+};
 use bevy_egui::egui::{Color32, Stroke};
 use std::collections::HashMap;
 
@@ -6,6 +9,10 @@ pub struct RenderStrokes<'a> {
     pub full: Stroke,
     pub none: Stroke,
     pub highlight: Stroke,
+    pub door_closed: Stroke,
+    pub door_locked: Stroke,
+    pub door_open: Stroke,
+    pub trigger: Stroke,
     mats: &'a HashMap<usize, (String, MaterialDefinition, String)>,
 }
 
@@ -15,13 +22,17 @@ impl<'a> RenderStrokes<'a> {
             full: Stroke::new(1.0, Color32::from_rgba_premultiplied(255, 255, 255, 255)),
             none: Stroke::new(1.0, Color32::from_rgba_premultiplied(32, 32, 32, 255)),
             highlight: Stroke::new(1.0, Color32::from_rgba_premultiplied(255, 255, 0, 255)),
+            door_closed: Stroke::new(3.0, Color32::from_rgb(160, 96, 32)),
+            door_locked: Stroke::new(3.0, Color32::from_rgb(255, 64, 32)),
+            door_open: Stroke::new(1.0, Color32::from_rgb(160, 96, 32)),
+            trigger: Stroke::new(2.0, Color32::from_rgb(255, 64, 255)),
             mats,
         }
     }
 
     pub fn wall_type(&self, wall: (RegionBoundaryType, u32)) -> Stroke {
         match wall.0 {
-            RegionBoundaryType::Wall | RegionBoundaryType::Opening => {
+            RegionBoundaryType::Wall | RegionBoundaryType::Opening | RegionBoundaryType::Door => {
                 let mat_idx = wall.1 as usize;
                 if let Some((_, MaterialDefinition::Color { r, g, b }, _)) = self.mats.get(&mat_idx)
                 {
@@ -41,6 +52,14 @@ impl<'a> RenderStrokes<'a> {
         }
     }
 
+    pub fn door(&self, state: DoorState) -> Stroke {
+        match state {
+            DoorState::Open => self.door_open,
+            DoorState::Closed => self.door_closed,
+            DoorState::Locked => self.door_locked,
+        }
+    }
+
     pub fn fill(&self, mat_idx: usize) -> Color32 {
         match self.mats.get(&mat_idx) {
             Some((_, MaterialDefinition::Color { r, g, b }, _)) => Color32::from_rgb(*r, *g, *b),
@@ -0,0 +1,319 @@
+use super::{
+    editor::MapEditor, render_strokes::RenderStrokes, scaling::Scaling,
+    wall_interaction::MapWallInteraction, wall_lines::wall_line, MapEditorMode,
+};
+use crate::{
+    module::Direction,
+    region::region_map::{RegionBoundaryType, RegionMap, RegionTileType},
+};
+use bevy_egui::egui::{Painter, PointerButton, Pos2, Rect, Response, Stroke};
+use std::collections::VecDeque;
+
+/// A rectangle or wall line being dragged out in the editor.
+#[derive(Clone, Copy)]
+pub struct ToolDrag {
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+    /// The wall a line started from
+    pub direction: Option<Direction>,
+    /// Dragged with the right mouse button
+    pub erase: bool,
+}
+
+impl<'a> MapEditor<'a> {
+    /// Room and Wall Line modes: drag with the left button to build, or the
+    /// right button to clear.
+    pub(super) fn drag_interact(
+        &mut self,
+        scale: &Scaling,
+        strokes: &RenderStrokes,
+        painter: &Painter,
+        response: &Response,
+    ) {
+        let pointer = response
+            .interact_pointer_pos()
+            .or_else(|| response.hover_pos())
+            .map(|pos| MapWallInteraction::new(scale, pos, self.map));
+
+        if let Some(pos) = &pointer {
+            if response.drag_started() {
+                let direction = pos.selected_wall;
+                if self.settings.mode == MapEditorMode::Room || direction.is_some() {
+                    self.settings.drag = Some(ToolDrag {
+                        start: (pos.tile_x, pos.tile_y),
+                        end: (pos.tile_x, pos.tile_y),
+                        direction,
+                        erase: response.dragged_by(PointerButton::Secondary),
+                    });
+                }
+            }
+            if let Some(drag) = &mut self.settings.drag {
+                drag.end = (pos.tile_x, pos.tile_y);
+            }
+        }
+
+        let drag = match self.settings.drag {
+            Some(drag) => drag,
+            None => {
+                // Show what a drag would start from
+                if let Some(pos) = pointer {
+                    match (self.settings.mode, pos.selected_wall) {
+                        (MapEditorMode::Line, Some(direction)) => painter.line_segment(
+                            wall_line(direction, pos.tile_x, pos.tile_y, scale),
+                            strokes.highlight,
+                        ),
+                        (MapEditorMode::Room, _) => self.preview_rect(
+                            (pos.tile_x, pos.tile_y),
+                            (pos.tile_x, pos.tile_y),
+                            scale,
+                            painter,
+                            strokes.highlight,
+                        ),
+                        _ => {}
+                    }
+                }
+                return;
+            }
+        };
+
+        if response.drag_released() {
+            self.settings.drag = None;
+            match (self.settings.mode, drag.direction) {
+                (MapEditorMode::Room, _) => self.room(drag.start, drag.end, drag.erase),
+                (MapEditorMode::Line, Some(direction)) => {
+                    for (x, y) in line_tiles(drag.start, drag.end, direction) {
+                        self.set_wall(x, y, direction, wall_or_erase(drag.erase));
+                    }
+                    self.map.prune_doors();
+                    self.map.needs_rebuild = true;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match (self.settings.mode, drag.direction) {
+            (MapEditorMode::Room, _) => {
+                self.preview_rect(drag.start, drag.end, scale, painter, strokes.highlight)
+            }
+            (MapEditorMode::Line, Some(direction)) => {
+                for (x, y) in line_tiles(drag.start, drag.end, direction) {
+                    painter.line_segment(wall_line(direction, x, y, scale), strokes.highlight);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn preview_rect(
+        &self,
+        start: (u32, u32),
+        end: (u32, u32),
+        scale: &Scaling,
+        painter: &Painter,
+        stroke: Stroke,
+    ) {
+        let ((x0, y0), (x1, y1)) = normalize(start, end);
+        let min = Pos2::new(x0 as f32 * scale.box_x, y0 as f32 * scale.box_y);
+        let max = Pos2::new((x1 + 1) as f32 * scale.box_x, (y1 + 1) as f32 * scale.box_y);
+        painter.rect_stroke(
+            Rect::from_two_pos(scale.to_screen * min, scale.to_screen * max),
+            0.0,
+            stroke,
+        );
+    }
+
+    /// Floors the rectangle and walls its edges, opening up any walls
+    /// inside it. Erasing clears the floor and every wall instead.
+    fn room(&mut self, start: (u32, u32), end: (u32, u32), erase: bool) {
+        let ((x0, y0), (x1, y1)) = normalize(start, end);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let idx = self.map.tile_idx(x as i32, y as i32);
+                if erase {
+                    self.map.tiles[idx].tile_type = RegionTileType::Empty;
+                } else {
+                    self.map.tiles[idx].tile_type = RegionTileType::Floor;
+                    self.map.tiles[idx].floor_material = self.settings.material as u32;
+                }
+
+                for direction in Direction::ALL {
+                    let edge = match direction {
+                        Direction::North => y == y0,
+                        Direction::South => y == y1,
+                        Direction::West => x == x0,
+                        Direction::East => x == x1,
+                    };
+                    if erase || !edge {
+                        // Tiles inside the room clear their own side of
+                        // each shared wall, so only edges reach outside it.
+                        let both_sides = erase && edge;
+                        self.set_boundary(x, y, direction, RegionBoundaryType::None, both_sides);
+                    } else {
+                        self.set_wall(x, y, direction, RegionBoundaryType::Wall);
+                    }
+                }
+            }
+        }
+        self.map.prune_doors();
+        self.map.needs_rebuild = true;
+    }
+
+    /// Sets one wall, and the facing wall of the next tile if walls are
+    /// double-sided.
+    fn set_wall(&mut self, x: u32, y: u32, direction: Direction, wall: RegionBoundaryType) {
+        let both_sides = self.settings.fill_walls;
+        self.set_boundary(x, y, direction, wall, both_sides);
+    }
+
+    fn set_boundary(
+        &mut self,
+        x: u32,
+        y: u32,
+        direction: Direction,
+        wall: RegionBoundaryType,
+        both_sides: bool,
+    ) {
+        let material = self.settings.material as u32;
+        set_edge(self.map, x, y, direction, (wall, material), both_sides);
+    }
+
+    /// Fill Floor and Fill Ceiling modes: left click paints the connected
+    /// area with the current material, right click clears it.
+    pub(super) fn fill_interact(
+        &mut self,
+        scale: &Scaling,
+        pointer_pos: Pos2,
+        response: &Response,
+    ) {
+        let erase = if response.clicked_by(PointerButton::Primary) {
+            false
+        } else if response.clicked_by(PointerButton::Secondary) {
+            true
+        } else {
+            return;
+        };
+        let pos = MapWallInteraction::new(scale, pointer_pos, self.map);
+        let ceiling = self.settings.mode == MapEditorMode::FillCeiling;
+        let material = self.settings.material as u32;
+        for idx in self.connected_tiles(pos.tile_x, pos.tile_y, ceiling) {
+            let tile = &mut self.map.tiles[idx];
+            match (ceiling, erase) {
+                (false, false) => {
+                    if tile.tile_type == RegionTileType::Empty {
+                        tile.tile_type = RegionTileType::Floor;
+                    }
+                    tile.floor_material = material;
+                }
+                (false, true) => tile.tile_type = RegionTileType::Empty,
+                (true, false) => {
+                    tile.has_ceiling = true;
+                    tile.ceiling_material = material;
+                }
+                (true, true) => tile.has_ceiling = false,
+            }
+        }
+        self.map.needs_rebuild = true;
+    }
+
+    /// Tiles reachable from x/y without crossing a wall or door, that look
+    /// the same as it: same floor (or ceiling) type and material.
+    fn connected_tiles(&self, x: u32, y: u32, ceiling: bool) -> Vec<usize> {
+        let map = &*self.map;
+        let key = |idx: usize| {
+            let tile = &map.tiles[idx];
+            if ceiling {
+                (
+                    tile.has_ceiling,
+                    tile.ceiling_material,
+                    RegionTileType::Empty,
+                )
+            } else {
+                (false, tile.floor_material, tile.tile_type)
+            }
+        };
+        let start = map.tile_idx(x as i32, y as i32);
+        let target = key(start);
+
+        let blocked =
+            |b: RegionBoundaryType| b == RegionBoundaryType::Wall || b == RegionBoundaryType::Door;
+        let mut visited = vec![false; map.tiles.len()];
+        let mut result = Vec::new();
+        let mut queue = VecDeque::new();
+        visited[start] = true;
+        queue.push_back((x as i32, y as i32));
+        while let Some((x, y)) = queue.pop_front() {
+            result.push(map.tile_idx(x, y));
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.delta_forward();
+                let (nx, ny) = (x + dx, y + dy);
+                if !map.in_bounds(nx, ny) {
+                    continue;
+                }
+                let here = map.tiles[map.tile_idx(x, y)].boundaries[direction.to_exit_index()].0;
+                let there = map.tiles[map.tile_idx(nx, ny)].boundaries
+                    [direction.opposite().to_exit_index()]
+                .0;
+                let idx = map.tile_idx(nx, ny);
+                if visited[idx] || blocked(here) || blocked(there) || key(idx) != target {
+                    continue;
+                }
+                visited[idx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+        result
+    }
+}
+
+/// Sets one side of an edge, and the facing side of the next tile too if
+/// `both_sides` is set.
+pub(super) fn set_edge(
+    map: &mut RegionMap,
+    x: u32,
+    y: u32,
+    direction: Direction,
+    edge: (RegionBoundaryType, u32),
+    both_sides: bool,
+) {
+    let idx = map.tile_idx(x as i32, y as i32);
+    map.tiles[idx].boundaries[direction.to_exit_index()] = edge;
+    if both_sides {
+        let (dx, dy) = direction.delta_forward();
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        if map.in_bounds(nx, ny) {
+            let idx = map.tile_idx(nx, ny);
+            map.tiles[idx].boundaries[direction.opposite().to_exit_index()] = edge;
+        }
+    }
+}
+
+fn normalize(a: (u32, u32), b: (u32, u32)) -> ((u32, u32), (u32, u32)) {
+    (
+        (u32::min(a.0, b.0), u32::min(a.1, b.1)),
+        (u32::max(a.0, b.0), u32::max(a.1, b.1)),
+    )
+}
+
+/// Walls on the north/south side run along the starting row; east/west
+/// walls run along the starting column.
+fn line_tiles(start: (u32, u32), end: (u32, u32), direction: Direction) -> Vec<(u32, u32)> {
+    match direction {
+        Direction::North | Direction::South => {
+            let (x0, x1) = (u32::min(start.0, end.0), u32::max(start.0, end.0));
+            (x0..=x1).map(|x| (x, start.1)).collect()
+        }
+        Direction::East | Direction::West => {
+            let (y0, y1) = (u32::min(start.1, end.1), u32::max(start.1, end.1));
+            (y0..=y1).map(|y| (start.0, y)).collect()
+        }
+    }
+}
+
+fn wall_or_erase(erase: bool) -> RegionBoundaryType {
+    if erase {
+        RegionBoundaryType::None
+    } else {
+        RegionBoundaryType::Wall
+    }
+}
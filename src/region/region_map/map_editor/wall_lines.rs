@@ -20,6 +20,13 @@ pub fn wall_opening(direction: Direction, x: u32, y: u32, scale: &Scaling) -> [[
     }
 }
 
+/// The middle of a wall, where a door sits inside its opening.
+pub fn wall_door(direction: Direction, x: u32, y: u32, scale: &Scaling) -> [Pos2; 2] {
+    let [start, end] = wall_line(direction, x, y, scale);
+    let inset = (end - start) * 0.3;
+    [start + inset, end - inset]
+}
+
 fn north_wall_line(x: u32, y: u32, scale: &Scaling) -> [Pos2; 2] {
     let px = x as f32 * scale.box_x;
     let py = y as f32 * scale.box_y;
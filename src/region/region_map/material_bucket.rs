@@ -9,6 +9,8 @@ pub enum FeatureType {
     Wall(Direction),
     Cube,
     Opening(Direction),
+    /// Facing direction, and whether the door is open
+    Door(Direction, bool),
 }
 
 pub struct MaterialBucket {
@@ -148,6 +150,9 @@ impl MaterialBucket {
                     );
                 }
             },
+            FeatureType::Door(dir, open) => {
+                add_door_geometry(bucket, dir, x, y, open);
+            }
             FeatureType::Cube => {
                 add_cube_geometry(
                     &mut bucket.vertices,
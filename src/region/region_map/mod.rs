@@ -3,8 +3,19 @@ use serde::{Deserialize, Serialize};
 mod material_bucket;
 use self::material_bucket::{FeatureType, MaterialBucket};
 use bevy::{prelude::*, render::mesh::VertexAttributeValues};
+mod doors;
+mod explored;
+pub use explored::ExploredMap;
+pub mod generator;
+mod resize;
+pub use resize::Anchor;
+mod visibility;
+pub use visibility::{field_of_view, line_of_sight, SightGrid};
 pub mod map_editor;
+mod pathfinding;
 use crate::module::Direction;
+pub use doors::{Door, DoorState};
+pub use pathfinding::{find_path, walk_distances, PathGrid};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RegionMap {
@@ -16,9 +27,11 @@ pub struct RegionMap {
     pub starting_location: (u32, u32, Direction),
     pub needs_rebuild: bool,
     pub map_start_event: String,
+    #[serde(default)]
+    pub doors: Vec<Door>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct RegionTile {
     pub tile_type: RegionTileType,
     pub has_ceiling: bool,
@@ -42,16 +55,43 @@ pub enum RegionBoundaryType {
     None,
     Wall,
     Opening,
+    Door,
+}
+
+impl RegionTileType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegionTileType::Empty => "Empty",
+            RegionTileType::Floor => "Floor",
+            RegionTileType::Solid => "Solid",
+        }
+    }
+}
+
+impl RegionBoundaryType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegionBoundaryType::None => "None",
+            RegionBoundaryType::Wall => "Wall",
+            RegionBoundaryType::Opening => "Opening",
+            RegionBoundaryType::Door => "Door",
+        }
+    }
 }
 
 impl RegionMap {
     pub fn default() -> Self {
-        const SIZE: (u32, u32) = (16, 16);
+        Self::new(16, 16)
+    }
+
+    /// An open floor of the given size, walled around the outside.
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = (width, height);
         let mut map = RegionMap {
             name: String::from("Test Map"),
             filename: String::from("new_map.ron"),
             index: 0,
-            size: (SIZE.0, SIZE.1),
+            size,
             tiles: vec![
                 RegionTile {
                     has_ceiling: false,
@@ -67,27 +107,28 @@ impl RegionMap {
                     entry_trigger: None,
                     exit_trigger: None,
                 };
-                (SIZE.0 as usize) * (SIZE.1 as usize)
+                (size.0 as usize) * (size.1 as usize)
             ],
-            starting_location: (SIZE.0 / 2, SIZE.1 / 2, Direction::North),
+            starting_location: (size.0 / 2, size.1 / 2, Direction::North),
             needs_rebuild: false,
             map_start_event: String::new(),
+            doors: Vec::new(),
         };
 
-        for x in 0..SIZE.0 {
-            // map.tiles[((0 * SIZE.0) + x) as usize].boundaries
+        for x in 0..size.0 {
+            // map.tiles[((0 * size.0) + x) as usize].boundaries
             // map.tiles[(0 + x) as usize].boundaries[Direction::North.to_exit_index() as usize].0 =
             map.tiles[x as usize].boundaries[Direction::North.to_exit_index() as usize].0 =
                 RegionBoundaryType::Wall;
-            map.tiles[(((SIZE.1 - 1) * SIZE.0) + x) as usize].boundaries
+            map.tiles[(((size.1 - 1) * size.0) + x) as usize].boundaries
                 [Direction::South.to_exit_index()]
             .0 = RegionBoundaryType::Wall;
         }
-        for y in 0..SIZE.1 {
-            // map.tiles[((y * SIZE.0) + 0) as usize].boundaries[Direction::West.to_exit_index()].0 =
-            map.tiles[(y * SIZE.0) as usize].boundaries[Direction::West.to_exit_index()].0 =
+        for y in 0..size.1 {
+            // map.tiles[((y * size.0) + 0) as usize].boundaries[Direction::West.to_exit_index()].0 =
+            map.tiles[(y * size.0) as usize].boundaries[Direction::West.to_exit_index()].0 =
                 RegionBoundaryType::Wall;
-            map.tiles[((y * SIZE.0) + (SIZE.0 - 1)) as usize].boundaries
+            map.tiles[((y * size.0) + (size.0 - 1)) as usize].boundaries
                 [Direction::East.to_exit_index()]
             .0 = RegionBoundaryType::Wall;
         }
@@ -99,6 +140,44 @@ impl RegionMap {
         (0.0 - x, y)
     }
 
+    /// Returns true if x/y are inside the map
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.size.0 as i32 && y < self.size.1 as i32
+    }
+
+    /// Obtain a tile index from x/y tile coordinates
+    pub fn tile_idx(&self, x: i32, y: i32) -> usize {
+        ((self.size.0 as i32 * y) + x) as usize
+    }
+
+    /// Can an entity standing on x/y step one tile in `direction`?
+    /// Walls block from either side, so a one-sided wall still stops you.
+    /// Doors block unless they are open.
+    pub fn can_move(&self, x: i32, y: i32, direction: Direction) -> bool {
+        let (dx, dy) = direction.delta_forward();
+        let (nx, ny) = (x + dx, y + dy);
+        if !self.in_bounds(x, y) || !self.in_bounds(nx, ny) {
+            return false;
+        }
+
+        let here = &self.tiles[self.tile_idx(x, y)];
+        let there = &self.tiles[self.tile_idx(nx, ny)];
+        if there.tile_type == RegionTileType::Solid {
+            return false;
+        }
+        if here.boundaries[direction.to_exit_index()].0 == RegionBoundaryType::Wall {
+            return false;
+        }
+        if there.boundaries[direction.opposite().to_exit_index()].0 == RegionBoundaryType::Wall {
+            return false;
+        }
+        if self.is_door(x, y, direction) && self.door_state(x, y, direction) != DoorState::Open {
+            return false;
+        }
+
+        true
+    }
+
     pub fn create_geometry(&self, meshes: &mut Assets<Mesh>) -> Vec<(u32, Handle<Mesh>)> {
         let mut bucket = MaterialBucket::new();
         let mut result = Vec::new();
@@ -218,6 +297,21 @@ impl RegionMap {
                         sy,
                     );
                 }
+
+                for direction in Direction::ALL {
+                    let (boundary, material) =
+                        self.tiles[tile_idx].boundaries[direction.to_exit_index()];
+                    if boundary == RegionBoundaryType::Door {
+                        let open =
+                            self.door_state(x as i32, y as i32, direction) == DoorState::Open;
+                        bucket.add_feature(
+                            FeatureType::Door(direction.opposite(), open),
+                            material,
+                            sx,
+                            sy,
+                        );
+                    }
+                }
             }
         }
 
@@ -248,3 +342,66 @@ impl RegionMap {
         result
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::{RegionBoundaryType, RegionMap};
+    use crate::module::Direction;
+
+    /// Walls one side of an edge, leaving the neighbour's side open.
+    pub fn wall(map: &mut RegionMap, x: i32, y: i32, direction: Direction) {
+        let idx = map.tile_idx(x, y);
+        map.tiles[idx].boundaries[direction.to_exit_index()].0 = RegionBoundaryType::Wall;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::wall;
+    use super::*;
+
+    #[test]
+    fn one_sided_walls_block_both_ways() {
+        let mut map = RegionMap::new(2, 1);
+        wall(&mut map, 0, 0, Direction::East);
+        assert!(!map.can_move(0, 0, Direction::East));
+        assert!(!map.can_move(1, 0, Direction::West));
+    }
+
+    #[test]
+    fn solid_tiles_block() {
+        let mut map = RegionMap::new(2, 1);
+        let idx = map.tile_idx(1, 0);
+        map.tiles[idx].tile_type = RegionTileType::Solid;
+        assert!(!map.can_move(0, 0, Direction::East));
+    }
+
+    #[test]
+    fn map_edges_block() {
+        let mut map = RegionMap::new(2, 2);
+        // Without the border walls `new` adds, only the bounds check is left
+        for tile in map.tiles.iter_mut() {
+            tile.boundaries
+                .iter_mut()
+                .for_each(|b| b.0 = RegionBoundaryType::None);
+        }
+        assert!(!map.can_move(0, 0, Direction::North));
+        assert!(!map.can_move(0, 0, Direction::West));
+        assert!(!map.can_move(1, 1, Direction::South));
+        assert!(!map.can_move(1, 1, Direction::East));
+        assert!(map.can_move(0, 0, Direction::East));
+    }
+
+    #[test]
+    fn doors_block_until_open() {
+        let mut map = RegionMap::new(2, 1);
+        let idx = map.tile_idx(0, 0);
+        map.tiles[idx].boundaries[Direction::East.to_exit_index()].0 = RegionBoundaryType::Door;
+        assert!(!map.can_move(0, 0, Direction::East));
+        map.set_door_state(0, 0, Direction::East, DoorState::Open);
+        assert!(map.can_move(0, 0, Direction::East));
+        assert!(map.can_move(1, 0, Direction::West));
+        map.set_door_state(1, 0, Direction::West, DoorState::Closed);
+        assert!(!map.can_move(1, 0, Direction::West));
+    }
+}
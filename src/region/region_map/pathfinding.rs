@@ -0,0 +1,149 @@
+use super::RegionMap;
+use crate::module::Direction;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+/// A grid of tiles that can be walked across, one tile at a time.
+pub trait PathGrid {
+    /// Tiles that can be stepped onto from x/y.
+    fn exits(&self, x: i32, y: i32) -> Vec<(i32, i32)>;
+}
+
+/// The shortest walk from `from` to `to` (A*), not including `from`.
+/// Returns None if `to` can't be reached.
+pub fn find_path<G: PathGrid>(
+    grid: &G,
+    from: (i32, i32),
+    to: (i32, i32),
+) -> Option<Vec<(i32, i32)>> {
+    let estimate = |(x, y): (i32, i32)| ((x - to.0).abs() + (y - to.1).abs()) as u32;
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut cost: HashMap<(i32, i32), u32> = HashMap::new();
+    cost.insert(from, 0);
+    open.push(Reverse((estimate(from), 0, from)));
+
+    while let Some(Reverse((_, steps, pos))) = open.pop() {
+        if pos == to {
+            let mut path = Vec::new();
+            let mut current = pos;
+            while current != from {
+                path.push(current);
+                current = came_from[&current];
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if steps > cost[&pos] {
+            // A shorter way here was already found
+            continue;
+        }
+        for next in grid.exits(pos.0, pos.1) {
+            let next_steps = steps + 1;
+            if cost.get(&next).map_or(true, |c| next_steps < *c) {
+                cost.insert(next, next_steps);
+                came_from.insert(next, pos);
+                open.push(Reverse((next_steps + estimate(next), next_steps, next)));
+            }
+        }
+    }
+    None
+}
+
+/// How many steps it takes to reach every tile within `max_steps` of
+/// `from` (Dijkstra, which is a flood fill when every step costs the same).
+pub fn walk_distances<G: PathGrid>(
+    grid: &G,
+    from: (i32, i32),
+    max_steps: u32,
+) -> HashMap<(i32, i32), u32> {
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    distances.insert(from, 0);
+    queue.push_back(from);
+    while let Some(pos) = queue.pop_front() {
+        let steps = distances[&pos];
+        if steps >= max_steps {
+            continue;
+        }
+        for next in grid.exits(pos.0, pos.1) {
+            if !distances.contains_key(&next) {
+                distances.insert(next, steps + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+impl PathGrid for RegionMap {
+    /// Walls and closed doors are in the way; openings and open doors
+    /// aren't.
+    fn exits(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        Direction::ALL
+            .iter()
+            .filter(|d| self.can_move(x, y, **d))
+            .map(|d| {
+                let (dx, dy) = d.delta_forward();
+                (x + dx, y + dy)
+            })
+            .collect()
+    }
+}
+
+impl RegionMap {
+    /// The tiles to walk through to get from one tile to another.
+    pub fn find_path(&self, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        if !self.in_bounds(to.0, to.1) {
+            return None;
+        }
+        find_path(self, from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::region_map::test_support::wall;
+
+    #[test]
+    fn straight_line() {
+        let map = RegionMap::new(5, 5);
+        let path = map.find_path((0, 0), (3, 0)).unwrap();
+        assert_eq!(path, vec![(1, 0), (2, 0), (3, 0)]);
+        assert_eq!(map.find_path((2, 2), (2, 2)), Some(Vec::new()));
+    }
+
+    #[test]
+    fn walks_around_walls() {
+        let mut map = RegionMap::new(3, 3);
+        // A wall between the left and middle columns, open at the bottom
+        wall(&mut map, 0, 0, Direction::East);
+        wall(&mut map, 0, 1, Direction::East);
+        let path = map.find_path((0, 0), (1, 0)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.last(), Some(&(1, 0)));
+        for pair in path.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!((a.0 - b.0).abs() + (a.1 - b.1).abs(), 1);
+        }
+    }
+
+    #[test]
+    fn no_path_into_a_closed_room() {
+        let mut map = RegionMap::new(3, 1);
+        wall(&mut map, 1, 0, Direction::East);
+        assert!(map.find_path((0, 0), (2, 0)).is_none());
+        assert!(map.find_path((0, 0), (5, 0)).is_none());
+    }
+
+    #[test]
+    fn distances_stop_at_the_limit() {
+        let map = RegionMap::new(5, 1);
+        let distances = walk_distances(&map, (0, 0), 2);
+        assert_eq!(distances.len(), 3);
+        assert_eq!(distances[&(2, 0)], 2);
+    }
+}
@@ -0,0 +1,129 @@
+use super::{RegionBoundaryType, RegionMap, RegionTile, RegionTileType};
+
+/// Which part of a map stays put when it is resized, along one axis.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Anchor {
+    /// Left or top
+    Start,
+    Middle,
+    /// Right or bottom
+    End,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 3] = [Anchor::Start, Anchor::Middle, Anchor::End];
+
+    /// How far existing tiles move when the axis changes length.
+    fn offset(&self, old: u32, new: u32) -> i32 {
+        let grow = new as i32 - old as i32;
+        match self {
+            Anchor::Start => 0,
+            Anchor::Middle => grow / 2,
+            Anchor::End => grow,
+        }
+    }
+}
+
+impl RegionMap {
+    /// Grows or crops the map to `size`. Tiles (and their triggers), doors
+    /// and the starting location keep their place relative to the anchored
+    /// edges; new space is empty. Returns how far tiles moved.
+    pub fn resize(&mut self, size: (u32, u32), anchor: (Anchor, Anchor)) -> (i32, i32) {
+        let size = (u32::max(size.0, 1), u32::max(size.1, 1));
+        let offset = (
+            anchor.0.offset(self.size.0, size.0),
+            anchor.1.offset(self.size.1, size.1),
+        );
+
+        let mut tiles = vec![empty_tile(); (size.0 * size.1) as usize];
+        for y in 0..self.size.1 as i32 {
+            for x in 0..self.size.0 as i32 {
+                let (nx, ny) = (x + offset.0, y + offset.1);
+                if nx >= 0 && ny >= 0 && nx < size.0 as i32 && ny < size.1 as i32 {
+                    tiles[(ny * size.0 as i32 + nx) as usize] =
+                        self.tiles[self.tile_idx(x, y)].clone();
+                }
+            }
+        }
+        self.tiles = tiles;
+        self.size = size;
+
+        let (sx, sy, facing) = self.starting_location;
+        self.starting_location = (
+            (sx as i32 + offset.0).clamp(0, size.0 as i32 - 1) as u32,
+            (sy as i32 + offset.1).clamp(0, size.1 as i32 - 1) as u32,
+            facing,
+        );
+
+        let doors = std::mem::take(&mut self.doors);
+        self.doors = doors
+            .into_iter()
+            .filter_map(|mut door| {
+                let (x, y) = (door.x as i32 + offset.0, door.y as i32 + offset.1);
+                if self.in_bounds(x, y) {
+                    door.x = x as u32;
+                    door.y = y as u32;
+                    Some(door)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.prune_doors();
+
+        self.needs_rebuild = true;
+        offset
+    }
+}
+
+fn empty_tile() -> RegionTile {
+    RegionTile {
+        tile_type: RegionTileType::Empty,
+        has_ceiling: false,
+        boundaries: [(RegionBoundaryType::None, 0); 4],
+        floor_material: 0,
+        ceiling_material: 0,
+        entry_trigger: None,
+        exit_trigger: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::Direction;
+
+    #[test]
+    fn grow_from_the_bottom_right() {
+        let mut map = RegionMap::new(4, 4);
+        map.tiles[0].entry_trigger = Some("Corner".to_string());
+        map.starting_location = (1, 2, Direction::East);
+
+        let offset = map.resize((6, 5), (Anchor::End, Anchor::End));
+        assert_eq!(offset, (2, 1));
+        assert_eq!(map.tiles.len(), 30);
+        assert_eq!(
+            map.tiles[map.tile_idx(2, 1)].entry_trigger,
+            Some("Corner".to_string())
+        );
+        assert_eq!(map.starting_location, (3, 3, Direction::East));
+        assert!(map.tiles[0].tile_type == RegionTileType::Empty);
+    }
+
+    #[test]
+    fn crop_from_the_middle() {
+        let mut map = RegionMap::new(5, 5);
+        map.tiles[map.tile_idx(2, 2)].entry_trigger = Some("Centre".to_string());
+        map.starting_location = (0, 0, Direction::North);
+
+        let offset = map.resize((3, 3), (Anchor::Middle, Anchor::Middle));
+        assert_eq!(offset, (-1, -1));
+        assert_eq!(map.tiles.len(), 9);
+        assert_eq!(
+            map.tiles[map.tile_idx(1, 1)].entry_trigger,
+            Some("Centre".to_string())
+        );
+        // The start was cropped away, so it is pulled back onto the map
+        assert_eq!(map.starting_location, (0, 0, Direction::North));
+    }
+}
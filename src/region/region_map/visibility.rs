@@ -0,0 +1,166 @@
+use super::{RegionBoundaryType, RegionMap, RegionTileType};
+use crate::module::Direction;
+
+/// A grid of tiles that can be looked across. Sight can be stopped by the
+/// edges between tiles or by the tiles themselves.
+pub trait SightGrid {
+    fn in_bounds(&self, x: i32, y: i32) -> bool;
+
+    /// Does the edge leaving x/y in `direction` stop sight?
+    fn blocks_sight(&self, x: i32, y: i32, direction: Direction) -> bool;
+
+    /// Opaque tiles can be seen, but not seen past.
+    fn is_opaque(&self, x: i32, y: i32) -> bool;
+}
+
+/// Can something standing in the middle of `from` see the middle of `to`?
+/// Walks the tiles a straight line between them passes through, so a wall
+/// anywhere along it gets in the way. A line passing exactly through a
+/// corner gets through if either side of the corner is clear.
+pub fn line_of_sight<G: SightGrid>(grid: &G, from: (i32, i32), to: (i32, i32)) -> bool {
+    if !grid.in_bounds(from.0, from.1) || !grid.in_bounds(to.0, to.1) {
+        return false;
+    }
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (nx, ny) = (dx.abs(), dy.abs());
+    let (sx, step_x) = if dx > 0 {
+        (1, Direction::East)
+    } else {
+        (-1, Direction::West)
+    };
+    let (sy, step_y) = if dy > 0 {
+        (1, Direction::South)
+    } else {
+        (-1, Direction::North)
+    };
+
+    let (mut x, mut y) = from;
+    let (mut ix, mut iy) = (0, 0);
+    while ix < nx || iy < ny {
+        // Which grid line does the ray cross next? Zero means both at once.
+        let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+        if decision == 0 {
+            let via_x = !grid.blocks_sight(x, y, step_x)
+                && !grid.is_opaque(x + sx, y)
+                && !grid.blocks_sight(x + sx, y, step_y);
+            let via_y = !grid.blocks_sight(x, y, step_y)
+                && !grid.is_opaque(x, y + sy)
+                && !grid.blocks_sight(x, y + sy, step_x);
+            if !via_x && !via_y {
+                return false;
+            }
+            x += sx;
+            y += sy;
+            ix += 1;
+            iy += 1;
+        } else if decision < 0 {
+            if grid.blocks_sight(x, y, step_x) {
+                return false;
+            }
+            x += sx;
+            ix += 1;
+        } else {
+            if grid.blocks_sight(x, y, step_y) {
+                return false;
+            }
+            y += sy;
+            iy += 1;
+        }
+
+        if (x, y) == to {
+            return true;
+        }
+        if grid.is_opaque(x, y) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Every tile within `range` of x/y that can be seen from it.
+pub fn field_of_view<G: SightGrid>(grid: &G, x: i32, y: i32, range: i32) -> Vec<(i32, i32)> {
+    let mut result = Vec::new();
+    for ty in y - range..=y + range {
+        for tx in x - range..=x + range {
+            if (tx - x).pow(2) + (ty - y).pow(2) <= range.pow(2)
+                && grid.in_bounds(tx, ty)
+                && line_of_sight(grid, (x, y), (tx, ty))
+            {
+                result.push((tx, ty));
+            }
+        }
+    }
+    result
+}
+
+impl SightGrid for RegionMap {
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        RegionMap::in_bounds(self, x, y)
+    }
+
+    /// Walls on either side of the edge, and doors that aren't open.
+    fn blocks_sight(&self, x: i32, y: i32, direction: Direction) -> bool {
+        let (dx, dy) = direction.delta_forward();
+        if !self.in_bounds(x, y) || !self.in_bounds(x + dx, y + dy) {
+            return true;
+        }
+        self.effective_boundary(x, y, direction) == RegionBoundaryType::Wall
+            || self.effective_boundary(x + dx, y + dy, direction.opposite())
+                == RegionBoundaryType::Wall
+    }
+
+    fn is_opaque(&self, x: i32, y: i32) -> bool {
+        !self.in_bounds(x, y) || self.tiles[self.tile_idx(x, y)].tile_type == RegionTileType::Solid
+    }
+}
+
+impl RegionMap {
+    /// Can someone on `from` see `to`?
+    pub fn can_see(&self, from: (i32, i32), to: (i32, i32)) -> bool {
+        line_of_sight(self, from, to)
+    }
+
+    /// Tiles visible from x/y, no further than `range` tiles away.
+    pub fn visible_tiles(&self, x: i32, y: i32, range: i32) -> Vec<(i32, i32)> {
+        field_of_view(self, x, y, range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::region_map::test_support::wall;
+
+    #[test]
+    fn open_room_is_visible() {
+        let map = RegionMap::new(5, 5);
+        assert!(map.can_see((0, 0), (4, 3)));
+        assert_eq!(map.visible_tiles(2, 2, 10).len(), 25);
+    }
+
+    #[test]
+    fn walls_block_from_either_side() {
+        let mut map = RegionMap::new(5, 1);
+        wall(&mut map, 2, 0, Direction::East);
+        assert!(map.can_see((0, 0), (2, 0)));
+        assert!(!map.can_see((0, 0), (3, 0)));
+        assert!(!map.can_see((4, 0), (1, 0)));
+    }
+
+    #[test]
+    fn solid_tiles_are_seen_but_not_seen_past() {
+        let mut map = RegionMap::new(5, 1);
+        map.tiles[2].tile_type = RegionTileType::Solid;
+        assert!(map.can_see((0, 0), (2, 0)));
+        assert!(!map.can_see((0, 0), (4, 0)));
+    }
+
+    #[test]
+    fn corners_need_one_clear_side() {
+        let mut map = RegionMap::new(2, 2);
+        wall(&mut map, 0, 0, Direction::East);
+        assert!(map.can_see((0, 0), (1, 1)));
+        wall(&mut map, 0, 0, Direction::South);
+        assert!(!map.can_see((0, 0), (1, 1)));
+    }
+}